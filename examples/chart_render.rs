@@ -0,0 +1,92 @@
+//! Times drawing a long price history into a terminal-sized chart, with and
+//! without downsampling to the chart's resolution.
+//!
+//!     cargo run --release --example chart_render [BARS] [FRAMES]
+
+use ordered_float::OrderedFloat;
+use std::time::{Duration, Instant};
+use tuinance::{
+    app::GraphType,
+    ticker::{Data, Ticker},
+    ui::{chart, theme::Theme},
+};
+use tui::{
+    backend::TestBackend,
+    symbols,
+    widgets::{Axis, Chart, Dataset, GraphType as TuiGraphType},
+    Terminal,
+};
+
+const WIDTH: u16 = 120;
+const HEIGHT: u16 = 40;
+
+/// A deterministic random walk of `len` closes.
+fn ticker(len: usize) -> Ticker {
+    let mut seed: u64 = 42;
+    let mut price = 100.0;
+    let mut closes = Vec::with_capacity(len);
+
+    for _ in 0..len {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        price *= 1.0 + ((seed >> 33) as f64 / (1u64 << 31) as f64 - 0.5) * 0.04;
+        closes.push(OrderedFloat::from(price));
+    }
+
+    let dates = (0..len).map(|idx| format!("day {}", idx)).collect();
+    let timestamps = (0..len as i64).map(|idx| idx * 86_400).collect();
+
+    let mut ticker = Ticker::new("BENCH".into());
+    ticker.set_data(Data::new(closes, dates, vec![0; len], timestamps));
+    ticker
+}
+
+fn time<F: FnMut(&mut Terminal<TestBackend>)>(frames: u32, mut draw: F) -> Duration {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let start = Instant::now();
+
+    for _ in 0..frames {
+        draw(&mut terminal);
+    }
+
+    start.elapsed() / frames
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1).map(|a| a.parse::<u32>().expect("expected a number"));
+    let bars = args.next().unwrap_or(15_000) as usize;
+    let frames = args.next().unwrap_or(50);
+
+    let ticker = ticker(bars);
+    let theme = Theme::default();
+    let history = Default::default();
+
+    let full = time(frames, |terminal| {
+        terminal.draw(|f| {
+            let points: Vec<(f64, f64)> = ticker.data().price_data().iter().enumerate()
+                .map(|(idx, p)| (idx as f64 + 1.0, f64::from(*p)))
+                .collect();
+
+            let (min, max) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+
+            let chart = Chart::new(vec![Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(TuiGraphType::Line)
+                    .data(&points)])
+                .x_axis(Axis::default().bounds([0.0, points.len() as f64]))
+                .y_axis(Axis::default().bounds([min, max]));
+
+            f.render_widget(chart, f.size());
+        }).unwrap();
+    });
+
+    let downsampled = time(frames, |terminal| {
+        terminal.draw(|f| chart::render(f, f.size(), &ticker, &GraphType::Price, &history, "", &theme)).unwrap();
+    });
+
+    println!("{} bars into a {}x{} chart, mean of {} frames", bars, WIDTH, HEIGHT, frames);
+    println!("{:>14}: {:?}", "all points", full);
+    println!("{:>14}: {:?} ({} points)", "downsampled", downsampled, chart::downsample(
+        (0..bars).map(|idx| (idx as f64, f64::from(ticker.data().price_data()[idx]))).collect(),
+        WIDTH as usize * 2,
+    ).len());
+}
@@ -0,0 +1,234 @@
+use crate::{alert::Notification, message::Message};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    process::Stdio,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The `[actions]` section: what runs when an alert fires. `command` and
+/// `webhook` can also be set per `[[alerts]]` entry.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ActionConfig {
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    webhook: Option<String>,
+    /// Seconds during which a rule firing again does not run its actions.
+    #[serde(default = "default_cooldown")]
+    cooldown: u64,
+    /// Actions run per minute across all rules.
+    #[serde(default = "default_rate_limit")]
+    rate_limit: usize,
+}
+
+fn default_cooldown() -> u64 {
+    300
+}
+
+fn default_rate_limit() -> usize {
+    10
+}
+
+impl Default for ActionConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            webhook: None,
+            cooldown: default_cooldown(),
+            rate_limit: default_rate_limit(),
+        }
+    }
+}
+
+impl ActionConfig {
+    pub fn command(&self) -> Option<&String> {
+        self.command.as_ref()
+    }
+
+    pub fn webhook(&self) -> Option<&String> {
+        self.webhook.as_ref()
+    }
+
+    pub fn cooldown(&self) -> u64 {
+        self.cooldown
+    }
+
+    pub fn rate_limit(&self) -> usize {
+        self.rate_limit
+    }
+}
+
+pub fn is_valid_webhook(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Runs the command and webhook of fired alerts, dropping repeats of the same
+/// alert within the cooldown and anything beyond the rate limit.
+pub struct Dispatcher {
+    config: ActionConfig,
+    client: reqwest::Client,
+    last_run: HashMap<String, Instant>,
+    recent: VecDeque<Instant>,
+}
+
+impl Dispatcher {
+    pub fn new(config: ActionConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            last_run: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    fn allow(&mut self, key: &str, now: Instant) -> bool {
+        let cooldown = Duration::from_secs(self.config.cooldown);
+
+        if self.last_run.get(key).map(|last| now.duration_since(*last) < cooldown).unwrap_or(false) {
+            return false;
+        }
+
+        while self.recent.front().map(|t| now.duration_since(*t) >= Duration::from_secs(60)).unwrap_or(false) {
+            self.recent.pop_front();
+        }
+
+        if self.recent.len() >= self.config.rate_limit {
+            return false;
+        }
+
+        self.last_run.insert(key.to_string(), now);
+        self.recent.push_back(now);
+        true
+    }
+
+    /// Spawns the actions of a fired alert on the current runtime; failures
+    /// are reported back as `Message::ActionFailed`. `command` and `webhook`
+    /// override the configured defaults.
+    pub fn dispatch(
+        &mut self,
+        notification: &Notification,
+        command: Option<&String>,
+        webhook: Option<&String>,
+        tx: &UnboundedSender<Message>,
+    ) {
+        let command = command.or_else(|| self.config.command()).cloned();
+        let webhook = webhook.or_else(|| self.config.webhook()).cloned();
+
+        if command.is_none() && webhook.is_none() {
+            return;
+        }
+
+        let key = format!("{} {}", notification.symbol, notification.condition);
+
+        if !self.allow(&key, Instant::now()) {
+            return;
+        }
+
+        let rule = notification.condition.to_string();
+        let time = notification.time.to_rfc3339();
+
+        if let Some(command) = command {
+            let mut process = tokio::process::Command::new("sh");
+
+            process
+                .arg("-c")
+                .arg(&command)
+                .env("TUINANCE_SYMBOL", &notification.symbol)
+                .env("TUINANCE_PRICE", notification.price.to_string())
+                .env("TUINANCE_RULE", &rule)
+                .env("TUINANCE_MESSAGE", &notification.message)
+                .env("TUINANCE_TIME", &time)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+
+            let tx = tx.clone();
+
+            tokio::spawn(async move {
+                let error = match process.status().await {
+                    Ok(status) if status.success() => return,
+                    Ok(status) => format!("alert command `{}` failed: {}", command, status),
+                    Err(e) => format!("could not run alert command `{}`: {}", command, e),
+                };
+
+                let _ = tx.send(Message::ActionFailed(error));
+            });
+        }
+
+        if let Some(webhook) = webhook {
+            let payload = serde_json::json!({
+                "symbol": notification.symbol,
+                "price": notification.price,
+                "rule": rule,
+                "message": notification.message,
+                "time": time,
+            });
+
+            let request = self.client.post(&webhook).timeout(WEBHOOK_TIMEOUT).json(&payload);
+            let tx = tx.clone();
+
+            tokio::spawn(async move {
+                let result = request.send().await.and_then(|response| response.error_for_status());
+
+                if let Err(e) = result {
+                    let _ = tx.send(Message::ActionFailed(format!("alert webhook {} failed: {}", webhook, e)));
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dispatcher(cooldown: u64, rate_limit: usize) -> Dispatcher {
+        Dispatcher::new(ActionConfig {
+            cooldown,
+            rate_limit,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn allow_drops_repeats_within_the_cooldown() {
+        let mut dispatcher = dispatcher(300, 10);
+        let start = Instant::now();
+
+        assert!(dispatcher.allow("AAPL above 200", start));
+        assert!(!dispatcher.allow("AAPL above 200", start + Duration::from_secs(299)));
+        assert!(dispatcher.allow("AAPL below 150", start + Duration::from_secs(299)));
+        assert!(dispatcher.allow("AAPL above 200", start + Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn allow_limits_actions_per_minute() {
+        let mut dispatcher = dispatcher(0, 3);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert!(dispatcher.allow("a", at(0)));
+        assert!(dispatcher.allow("b", at(10)));
+        assert!(dispatcher.allow("c", at(20)));
+        assert!(!dispatcher.allow("d", at(30)));
+
+        // the first action leaves the window after a minute
+        assert!(!dispatcher.allow("d", at(59)));
+        assert!(dispatcher.allow("d", at(60)));
+        assert!(!dispatcher.allow("e", at(61)));
+    }
+
+    #[test]
+    fn allow_does_not_count_dropped_actions() {
+        let mut dispatcher = dispatcher(300, 2);
+        let start = Instant::now();
+
+        assert!(dispatcher.allow("a", start));
+        assert!(!dispatcher.allow("a", start + Duration::from_secs(1)));
+        assert!(dispatcher.allow("b", start + Duration::from_secs(2)));
+    }
+}
@@ -0,0 +1,486 @@
+use crate::ticker::Ticker;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use std::{fmt, str::FromStr};
+
+/// Number of fired alerts kept in the history pane.
+const HISTORY_LEN: usize = 100;
+
+/// Number of failed alert actions kept for the alerts view.
+const ERRORS_LEN: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Either,
+}
+
+/// What an alert rule watches for. The textual form, used both in the
+/// configuration and the alert prompt, is one of:
+///
+/// * `above 200`, `below 150`
+/// * `move 5`, `move +5`, `move -5`: percent move from the day's open
+/// * `move 5 from 180`: percent move from a reference price
+/// * `volume 3`: day volume at least 3 times the average daily volume
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    Above(f64),
+    Below(f64),
+    Move {
+        percent: f64,
+        direction: Direction,
+        reference: Option<f64>,
+    },
+    VolumeSpike(f64),
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+
+        let number = |idx: usize| -> Result<f64, String> {
+            let word = words.get(idx).ok_or_else(|| format!("\"{}\" requires a number", words[0]))?;
+            word.parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| format!("invalid number \"{}\"", word))
+        };
+
+        let condition = match words.first().map(|w| w.to_lowercase()).as_deref() {
+            Some("above") => Condition::Above(number(1)?),
+            Some("below") => Condition::Below(number(1)?),
+            Some("move") => {
+                let percent = number(1)?;
+
+                let direction = match words[1].chars().next() {
+                    Some('+') => Direction::Up,
+                    Some('-') => Direction::Down,
+                    _ => Direction::Either,
+                };
+
+                let reference = match words.get(2).map(|w| w.to_lowercase()).as_deref() {
+                    Some("from") => Some(number(3)?),
+                    Some(other) => return Err(format!("unexpected \"{}\", expected \"from\"", other)),
+                    None => None,
+                };
+
+                Condition::Move {
+                    percent: percent.abs(),
+                    direction,
+                    reference,
+                }
+            }
+            Some("volume") => Condition::VolumeSpike(number(1)?),
+            Some(other) => return Err(format!("unknown condition \"{}\" (expected above, below, move or volume)", other)),
+            None => return Err("empty condition".into()),
+        };
+
+        let expected = match condition {
+            Condition::Move { reference: Some(_), .. } => 4,
+            _ => 2,
+        };
+
+        match words.len() > expected {
+            true => Err(format!("unexpected \"{}\"", words[expected])),
+            false => Ok(condition),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Above(price) => write!(f, "above {}", price),
+            Condition::Below(price) => write!(f, "below {}", price),
+            Condition::Move { percent, direction, reference } => {
+                let sign = match direction {
+                    Direction::Up => "+",
+                    Direction::Down => "-",
+                    Direction::Either => "",
+                };
+
+                write!(f, "move {}{}", sign, percent)?;
+
+                match reference {
+                    Some(reference) => write!(f, " from {}", reference),
+                    None => Ok(()),
+                }
+            }
+            Condition::VolumeSpike(multiple) => write!(f, "volume {}", multiple),
+        }
+    }
+}
+
+/// An `[[alerts]]` entry of the configuration.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlertConfig {
+    symbol: String,
+    when: String,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    webhook: Option<String>,
+}
+
+impl AlertConfig {
+    pub fn symbol(&self) -> &String {
+        &self.symbol
+    }
+
+    pub fn when(&self) -> &String {
+        &self.when
+    }
+
+    pub fn command(&self) -> Option<&String> {
+        self.command.as_ref()
+    }
+
+    pub fn webhook(&self) -> Option<&String> {
+        self.webhook.as_ref()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    id: usize,
+    symbol: String,
+    condition: Condition,
+    /// Whether the condition held on the last evaluation; rules only fire
+    /// when it starts to hold.
+    triggered: bool,
+    /// Overrides of the `[actions]` command and webhook.
+    command: Option<String>,
+    webhook: Option<String>,
+}
+
+impl Rule {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn symbol(&self) -> &String {
+        &self.symbol
+    }
+
+    pub fn condition(&self) -> &Condition {
+        &self.condition
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.triggered
+    }
+
+    pub fn command(&self) -> Option<&String> {
+        self.command.as_ref()
+    }
+
+    pub fn webhook(&self) -> Option<&String> {
+        self.webhook.as_ref()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.symbol, self.condition)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub rule_id: usize,
+    pub symbol: String,
+    pub condition: Condition,
+    pub price: f64,
+    pub message: String,
+    pub time: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Alerts {
+    rules: Vec<Rule>,
+    history: Vec<Notification>,
+    /// Alerts fired since startup, including those dropped from the history.
+    fired: usize,
+    errors: Vec<String>,
+    next_id: usize,
+}
+
+impl Alerts {
+    pub fn from_config(alerts: &[AlertConfig]) -> Result<Self, String> {
+        let mut store = Self::default();
+
+        for alert in alerts {
+            let condition = alert.when.parse().map_err(|e| format!("alert for {}: {}", alert.symbol, e))?;
+            let id = store.add(&alert.symbol, condition);
+
+            if let Some(rule) = store.rules.iter_mut().find(|r| r.id == id) {
+                rule.command = alert.command.clone();
+                rule.webhook = alert.webhook.clone();
+            }
+        }
+
+        Ok(store)
+    }
+
+    pub fn add(&mut self, symbol: &str, condition: Condition) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.rules.push(Rule {
+            id,
+            symbol: symbol.to_uppercase(),
+            condition,
+            triggered: false,
+            command: None,
+            webhook: None,
+        });
+
+        id
+    }
+
+    /// Parses `[SYMBOL] CONDITION` as typed in the alert prompt, using
+    /// `default_symbol` when the symbol is left out.
+    pub fn add_from_str(&mut self, input: &str, default_symbol: &str) -> Result<usize, String> {
+        let input = input.trim();
+        let first = input.split_whitespace().next().unwrap_or_default();
+
+        let (symbol, condition) = match input.parse::<Condition>().is_err() {
+            true => (first, input[first.len()..].trim()),
+            false => (default_symbol, input),
+        };
+
+        let condition = condition.parse()?;
+        Ok(self.add(symbol, condition))
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.rules.retain(|r| r.id != id);
+    }
+
+    pub fn rules(&self) -> &Vec<Rule> {
+        &self.rules
+    }
+
+    pub fn rule(&self, id: usize) -> Option<&Rule> {
+        self.rules.iter().find(|r| r.id == id)
+    }
+
+    /// Fired alerts, oldest first.
+    pub fn history(&self) -> &Vec<Notification> {
+        &self.history
+    }
+
+    pub fn fired(&self) -> usize {
+        self.fired
+    }
+
+    /// Failed alert actions, oldest first.
+    pub fn errors(&self) -> &Vec<String> {
+        &self.errors
+    }
+
+    pub fn record_error(&mut self, error: String) {
+        self.errors.push(error);
+
+        if self.errors.len() > ERRORS_LEN {
+            self.errors.remove(0);
+        }
+    }
+
+    pub fn is_triggered(&self, symbol: &str) -> bool {
+        self.rules.iter().any(|r| r.triggered && r.symbol == symbol)
+    }
+
+    /// Checks every rule of the ticker against its latest price and the
+    /// streamed day `volume`, returning the alerts that just fired.
+    pub fn evaluate(&mut self, ticker: &Ticker, volume: u64) -> Vec<Notification> {
+        let price = ticker.realtime_price();
+        let data = ticker.data();
+
+        let open = data.open_data().last().copied();
+
+        let volume = match volume {
+            0 => data.volume_data().last().copied().unwrap_or(0),
+            v => v,
+        };
+
+        // average of the completed days, the last bar being today's
+        let past = &data.volume_data()[..data.volume_data().len().saturating_sub(1)];
+        let average = match past.is_empty() {
+            true => None,
+            false => Some(past.iter().sum::<u64>() as f64 / past.len() as f64),
+        };
+
+        let mut fired = vec![];
+
+        for rule in self.rules.iter_mut().filter(|r| &r.symbol == ticker.identifier()) {
+            let hit = match rule.condition {
+                Condition::Above(level) => price > level,
+                Condition::Below(level) => price < level,
+                Condition::Move { percent, direction, reference } => {
+                    match reference.or(open).filter(|r| *r > 0.0) {
+                        Some(base) => {
+                            let change = (price - base) / base * 100.0;
+
+                            match direction {
+                                Direction::Up => change >= percent,
+                                Direction::Down => change <= -percent,
+                                Direction::Either => change.abs() >= percent,
+                            }
+                        }
+                        None => false,
+                    }
+                }
+                Condition::VolumeSpike(multiple) => match average {
+                    Some(average) if average > 0.0 => volume as f64 >= average * multiple,
+                    _ => false,
+                },
+            };
+
+            if hit && !rule.triggered {
+                fired.push(Notification {
+                    rule_id: rule.id,
+                    symbol: rule.symbol.clone(),
+                    condition: rule.condition,
+                    price,
+                    message: format!("{} {} (last {:.2})", rule.symbol, rule.condition, price),
+                    time: Local::now(),
+                });
+            }
+
+            rule.triggered = hit;
+        }
+
+        self.fired += fired.len();
+        self.history.extend(fired.iter().cloned());
+
+        if self.history.len() > HISTORY_LEN {
+            let excess = self.history.len() - HISTORY_LEN;
+            self.history.drain(..excess);
+        }
+
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticker::Data;
+    use ordered_float::OrderedFloat;
+
+    /// AAPL with daily closes (and opens) of `closes` and volumes `volumes`.
+    fn ticker(closes: &[f64], volumes: &[u64]) -> Ticker {
+        let mut ticker = Ticker::new("AAPL".into());
+        ticker.set_data(Data::new(
+            closes.iter().map(|c| OrderedFloat::from(*c)).collect(),
+            closes.iter().map(|_| String::new()).collect(),
+            volumes.to_vec(),
+            (0..closes.len() as i64).collect(),
+        ));
+        ticker
+    }
+
+    #[test]
+    fn condition_round_trips() {
+        for text in ["above 200", "below 150.5", "move 5", "move +5", "move -2.5", "move 5 from 180", "volume 3"] {
+            let condition: Condition = text.parse().unwrap();
+            assert_eq!(condition.to_string(), text);
+            assert_eq!(condition.to_string().parse::<Condition>(), Ok(condition));
+        }
+
+        assert_eq!(
+            "MOVE -5 FROM 180".parse(),
+            Ok(Condition::Move { percent: 5.0, direction: Direction::Down, reference: Some(180.0) }),
+        );
+    }
+
+    #[test]
+    fn condition_rejects_invalid_input() {
+        assert_eq!("".parse::<Condition>(), Err("empty condition".into()));
+        assert_eq!("above".parse::<Condition>(), Err("\"above\" requires a number".into()));
+        assert_eq!("below ten".parse::<Condition>(), Err("invalid number \"ten\"".into()));
+        assert_eq!("above inf".parse::<Condition>(), Err("invalid number \"inf\"".into()));
+        assert_eq!("move 5 to 180".parse::<Condition>(), Err("unexpected \"to\", expected \"from\"".into()));
+        assert_eq!("move 5 from".parse::<Condition>(), Err("\"move\" requires a number".into()));
+        assert_eq!("above 200 now".parse::<Condition>(), Err("unexpected \"now\"".into()));
+        assert!("around 200".parse::<Condition>().unwrap_err().starts_with("unknown condition \"around\""));
+    }
+
+    #[test]
+    fn evaluate_fires_on_crossing_and_re_arms() {
+        let mut alerts = Alerts::default();
+        let id = alerts.add("aapl", Condition::Above(100.0));
+        let mut ticker = ticker(&[90.0], &[0]);
+
+        assert!(alerts.evaluate(&ticker, 0).is_empty());
+
+        ticker.set_realtime_price(101.0);
+        let fired = alerts.evaluate(&ticker, 0);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule_id, id);
+        assert_eq!(fired[0].message, "AAPL above 100 (last 101.00)");
+        assert!(alerts.is_triggered("AAPL"));
+
+        // still above: no new alert
+        ticker.set_realtime_price(105.0);
+        assert!(alerts.evaluate(&ticker, 0).is_empty());
+
+        // back below re-arms the rule
+        ticker.set_realtime_price(99.0);
+        assert!(alerts.evaluate(&ticker, 0).is_empty());
+        assert!(!alerts.is_triggered("AAPL"));
+
+        ticker.set_realtime_price(102.0);
+        assert_eq!(alerts.evaluate(&ticker, 0).len(), 1);
+        assert_eq!(alerts.fired(), 2);
+        assert_eq!(alerts.history().len(), 2);
+    }
+
+    #[test]
+    fn evaluate_ignores_other_symbols() {
+        let mut alerts = Alerts::default();
+        alerts.add("MSFT", Condition::Below(1000.0));
+
+        assert!(alerts.evaluate(&ticker(&[90.0], &[0]), 0).is_empty());
+        assert!(!alerts.is_triggered("MSFT"));
+    }
+
+    #[test]
+    fn evaluate_moves_from_the_open_or_a_reference() {
+        let mut alerts = Alerts::default();
+        alerts.add("AAPL", "move +5".parse().unwrap());
+        alerts.add("AAPL", "move -5".parse().unwrap());
+        alerts.add("AAPL", "move 10 from 99".parse().unwrap());
+
+        let mut ticker = ticker(&[100.0], &[0]);
+
+        ticker.set_realtime_price(104.0);
+        assert!(alerts.evaluate(&ticker, 0).is_empty());
+
+        ticker.set_realtime_price(95.0);
+        let fired = alerts.evaluate(&ticker, 0);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].condition.to_string(), "move -5");
+
+        // neither 104 nor 95 is 10% away from 99, 180 is
+        ticker.set_realtime_price(180.0);
+        let fired: Vec<String> = alerts.evaluate(&ticker, 0).iter().map(|n| n.condition.to_string()).collect();
+        assert_eq!(fired, vec!["move +5", "move 10 from 99"]);
+    }
+
+    #[test]
+    fn evaluate_volume_against_past_days() {
+        let mut alerts = Alerts::default();
+        alerts.add("AAPL", Condition::VolumeSpike(3.0));
+
+        let ticker = ticker(&[10.0, 10.0, 10.0], &[100, 200, 50]);
+
+        assert!(alerts.evaluate(&ticker, 0).is_empty());
+        assert!(alerts.evaluate(&ticker, 449).is_empty());
+        assert_eq!(alerts.evaluate(&ticker, 450).len(), 1);
+    }
+}
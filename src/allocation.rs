@@ -0,0 +1,247 @@
+use crate::{portfolio::Summary, ticker::Ticker};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+/// Dimension the holdings are grouped by in the allocation view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grouping {
+    Sector,
+    Industry,
+    AssetType,
+    Currency,
+    Tag,
+}
+
+impl Grouping {
+    pub const ALL: [Grouping; 5] = [
+        Grouping::Sector,
+        Grouping::Industry,
+        Grouping::AssetType,
+        Grouping::Currency,
+        Grouping::Tag,
+    ];
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|g| *g == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Key of the grouping in `[portfolio.targets]`.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Grouping::Sector => "sector",
+            Grouping::Industry => "industry",
+            Grouping::AssetType => "type",
+            Grouping::Currency => "currency",
+            Grouping::Tag => "tag",
+        }
+    }
+}
+
+impl FromStr for Grouping {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.iter().find(|g| g.key() == s.to_lowercase()).copied().ok_or(())
+    }
+}
+
+impl fmt::Display for Grouping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Grouping::Sector => write!(f, "Sector"),
+            Grouping::Industry => write!(f, "Industry"),
+            Grouping::AssetType => write!(f, "Asset type"),
+            Grouping::Currency => write!(f, "Currency"),
+            Grouping::Tag => write!(f, "Tag"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Slice {
+    pub name: String,
+    pub value: f64,
+    /// Percentage of the total market value.
+    pub weight: f64,
+    /// Target percentage from the configuration, if any.
+    pub target: Option<f64>,
+}
+
+impl Slice {
+    /// Percentage points above (positive) or below the target.
+    pub fn drift(&self) -> Option<f64> {
+        self.target.map(|t| self.weight - t)
+    }
+
+    /// Amount to buy (positive) or sell to reach the target weight.
+    pub fn trade(&self, total: f64) -> Option<f64> {
+        self.target.map(|t| total * t / 100.0 - self.value)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Allocation {
+    pub total: f64,
+    pub slices: Vec<Slice>,
+}
+
+/// Groups the market value of the holdings. Values are summed as-is, without
+/// currency conversion, and a holding with several tags counts towards each.
+pub fn allocate(
+    summary: &Summary,
+    tickers: &[Ticker],
+    grouping: Grouping,
+    tags: &HashMap<String, Vec<String>>,
+    targets: Option<&HashMap<String, f64>>,
+) -> Allocation {
+    let mut slices: Vec<Slice> = vec![];
+    let total: f64 = summary.holdings.iter().map(|h| h.market_value).sum();
+
+    for holding in summary.holdings.iter() {
+        let info = tickers.iter().find(|t| t.identifier() == &holding.symbol).map(|t| t.info());
+
+        let names: Vec<String> = match grouping {
+            Grouping::Sector => vec![info.and_then(|i| i.sector()).unwrap_or("Unknown").to_string()],
+            Grouping::Industry => vec![info.and_then(|i| i.industry()).unwrap_or("Unknown").to_string()],
+            Grouping::AssetType => vec![info.and_then(|i| i.asset_type()).unwrap_or("Unknown").to_string()],
+            Grouping::Currency => vec![holding.currency.clone()],
+            Grouping::Tag => match tags.get(&holding.symbol) {
+                Some(tags) if !tags.is_empty() => tags.clone(),
+                _ => vec!["Untagged".into()],
+            },
+        };
+
+        for name in names {
+            match slices.iter_mut().find(|s| s.name == name) {
+                Some(slice) => slice.value += holding.market_value,
+                None => slices.push(Slice {
+                    name,
+                    value: holding.market_value,
+                    weight: 0.0,
+                    target: None,
+                }),
+            }
+        }
+    }
+
+    if let Some(targets) = targets {
+        for (name, target) in targets.iter() {
+            match slices.iter_mut().find(|s| s.name.eq_ignore_ascii_case(name)) {
+                Some(slice) => slice.target = Some(*target),
+                None => slices.push(Slice {
+                    name: name.clone(),
+                    value: 0.0,
+                    weight: 0.0,
+                    target: Some(*target),
+                }),
+            }
+        }
+    }
+
+    for slice in slices.iter_mut() {
+        if total > 0.0 {
+            slice.weight = slice.value / total * 100.0;
+        }
+    }
+
+    slices.sort_by(|a, b| {
+        b.value
+            .partial_cmp(&a.value)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Allocation { total, slices }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::Holding;
+
+    fn holding(symbol: &str, currency: &str, market_value: f64) -> Holding {
+        Holding {
+            symbol: symbol.into(),
+            name: String::new(),
+            currency: currency.into(),
+            shares: 1.0,
+            price: market_value,
+            cost_basis: market_value,
+            market_value,
+            gain: 0.0,
+            gain_pct: 0.0,
+            day_change: 0.0,
+            day_change_pct: 0.0,
+            weight: 0.0,
+        }
+    }
+
+    fn summary() -> Summary {
+        Summary {
+            holdings: vec![holding("AAPL", "USD", 500.0), holding("SAP", "EUR", 300.0), holding("MSFT", "USD", 200.0)],
+            totals: vec![],
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn allocate_groups_holdings() {
+        let allocation = allocate(&summary(), &[], Grouping::Currency, &HashMap::new(), None);
+
+        assert_eq!(allocation.total, 1000.0);
+
+        let slices: Vec<(&str, f64, f64)> = allocation.slices.iter().map(|s| (s.name.as_str(), s.value, s.weight)).collect();
+        assert_eq!(slices, vec![("USD", 700.0, 70.0), ("EUR", 300.0, 30.0)]);
+
+        // sectors of tickers without a profile are unknown
+        let allocation = allocate(&summary(), &[Ticker::new("AAPL".into())], Grouping::Sector, &HashMap::new(), None);
+        assert_eq!(allocation.slices.len(), 1);
+        assert_eq!(allocation.slices[0].name, "Unknown");
+        assert_eq!(allocation.slices[0].weight, 100.0);
+    }
+
+    #[test]
+    fn allocate_counts_each_tag_and_untagged_holdings() {
+        let mut tags = HashMap::new();
+        tags.insert("AAPL".to_string(), vec!["tech".to_string(), "core".to_string()]);
+        tags.insert("MSFT".to_string(), vec!["tech".to_string()]);
+        tags.insert("SAP".to_string(), vec![]);
+
+        let allocation = allocate(&summary(), &[], Grouping::Tag, &tags, None);
+        let slices: Vec<(&str, f64)> = allocation.slices.iter().map(|s| (s.name.as_str(), s.value)).collect();
+
+        assert_eq!(slices, vec![("tech", 700.0), ("core", 500.0), ("Untagged", 300.0)]);
+        assert_eq!(allocation.total, 1000.0);
+    }
+
+    #[test]
+    fn allocate_drifts_against_targets() {
+        let mut targets = HashMap::new();
+        targets.insert("usd".to_string(), 60.0);
+        targets.insert("EUR".to_string(), 30.0);
+        targets.insert("JPY".to_string(), 10.0);
+
+        let allocation = allocate(&summary(), &[], Grouping::Currency, &HashMap::new(), Some(&targets));
+        let slice = |name: &str| allocation.slices.iter().find(|s| s.name == name).unwrap();
+
+        assert!(close(slice("USD").drift().unwrap(), 10.0));
+        assert!(close(slice("USD").trade(allocation.total).unwrap(), -100.0));
+        assert!(close(slice("EUR").drift().unwrap(), 0.0));
+        assert!(close(slice("JPY").drift().unwrap(), -10.0));
+        assert!(close(slice("JPY").trade(allocation.total).unwrap(), 100.0));
+        assert_eq!(allocation.slices.last().unwrap().name, "JPY");
+
+        let untargeted = allocate(&summary(), &[], Grouping::Currency, &HashMap::new(), None);
+        assert!(untargeted.slices.iter().all(|s| s.drift().is_none() && s.trade(1000.0).is_none()));
+    }
+
+    #[test]
+    fn grouping_cycles_and_parses_its_key() {
+        assert_eq!(Grouping::Tag.next(), Grouping::Sector);
+        assert_eq!("TYPE".parse(), Ok(Grouping::AssetType));
+        assert_eq!("region".parse::<Grouping>(), Err(()));
+    }
+}
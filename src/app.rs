@@ -1,16 +1,259 @@
+use crate::range::DateRange;
+use std::{ops::Range, str::FromStr};
+
 #[derive(Debug, Clone)]
 pub enum GraphType {
     Price,
-    Volume
+    Volume,
+    Portfolio,
+}
+
+impl FromStr for GraphType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "price" => Ok(GraphType::Price),
+            "volume" => Ok(GraphType::Volume),
+            "portfolio" => Ok(GraphType::Portfolio),
+            _ => Err(()),
+        }
+    }
 }
 
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum State {
     Main,
     ModeSelection,
+    Portfolio,
+    Allocation,
+    Alerts,
+    Correlation,
+    Heatmap,
+}
+
+/// Fewest bars the chart can be zoomed in to.
+const MIN_BARS: usize = 5;
+
+/// Window of the loaded bars shown by the chart.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Viewport {
+    /// Number of bars shown, `None` for all of them.
+    width: Option<usize>,
+    /// Bars hidden to the right of the window.
+    offset: usize,
+}
+
+impl Viewport {
+    /// Indices of the visible bars out of `len`.
+    pub fn range(&self, len: usize) -> Range<usize> {
+        let width = self.width.unwrap_or(len).min(len);
+        let end = len - self.offset.min(len - width);
+
+        end - width..end
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.width.is_some()
+    }
+
+    /// Halves the window around its center.
+    pub fn zoom_in(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+
+        let range = self.range(len);
+        let width = (range.len() / 2).max(MIN_BARS).min(range.len());
+
+        self.offset = len - range.end + (range.len() - width) / 2;
+        self.width = Some(width);
+    }
+
+    /// Doubles the window around its center, showing everything once it
+    /// covers all bars.
+    pub fn zoom_out(&mut self, len: usize) {
+        let range = self.range(len);
+        let width = range.len() * 2;
+
+        match width < len {
+            true => {
+                self.offset = (len - range.end).saturating_sub((width - range.len()) / 2).min(len - width);
+                self.width = Some(width);
+            }
+            false => self.reset(),
+        }
+    }
+
+    /// Moves the window a quarter of its width towards older bars, or newer
+    /// ones when `forward`.
+    pub fn pan(&mut self, len: usize, forward: bool) {
+        let range = self.range(len);
+        let step = (range.len() / 4).max(1);
+
+        self.offset = match forward {
+            true => self.offset.min(len - range.len()).saturating_sub(step),
+            false => (self.offset + step).min(len - range.len()),
+        };
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// What the text typed into the prompt line is used for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptKind {
+    Alert,
+    /// Custom date range of the chart.
+    Range,
+}
+
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    kind: PromptKind,
+    input: String,
+    error: Option<String>,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind) -> Self {
+        Self {
+            kind,
+            input: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn kind(&self) -> PromptKind {
+        self.kind
+    }
+
+    pub fn input(&self) -> &String {
+        &self.input
+    }
+
+    pub fn error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.input.push(c);
+        self.error = None;
+    }
+
+    pub fn pop(&mut self) {
+        self.input.pop();
+        self.error = None;
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self.kind {
+            PromptKind::Alert => "New alert: ",
+            PromptKind::Range => "Date range: ",
+        }
+    }
+
+    /// Accepted input, shown above the prompt line.
+    pub fn hint(&self) -> &'static str {
+        match self.kind {
+            PromptKind::Alert => "[SYMBOL] above|below PRICE, move [+|-]PERCENT [from PRICE], volume MULTIPLE",
+            PromptKind::Range => DateRange::SYNTAX,
+        }
+    }
 }
 
 pub struct App {
     state: State,
     graph_type: GraphType,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_shows_everything_by_default() {
+        let viewport = Viewport::default();
+
+        assert!(!viewport.is_zoomed());
+        assert_eq!(viewport.range(100), 0..100);
+        assert_eq!(viewport.range(0), 0..0);
+    }
+
+    #[test]
+    fn zoom_in_halves_around_the_center() {
+        let mut viewport = Viewport::default();
+        viewport.zoom_in(100);
+
+        assert!(viewport.is_zoomed());
+        assert_eq!(viewport.range(100), 25..75);
+
+        viewport.zoom_out(100);
+        assert!(!viewport.is_zoomed());
+        assert_eq!(viewport.range(100), 0..100);
+    }
+
+    #[test]
+    fn zoom_in_stops_at_the_minimum_width() {
+        let mut viewport = Viewport::default();
+
+        for _ in 0..10 {
+            viewport.zoom_in(100);
+        }
+
+        assert_eq!(viewport.range(100).len(), MIN_BARS);
+
+        let mut viewport = Viewport::default();
+        viewport.zoom_in(3);
+        assert_eq!(viewport.range(3), 0..3);
+
+        let mut viewport = Viewport::default();
+        viewport.zoom_in(0);
+        assert!(!viewport.is_zoomed());
+    }
+
+    #[test]
+    fn pan_stops_at_either_end() {
+        let mut viewport = Viewport::default();
+        viewport.zoom_in(100);
+
+        viewport.pan(100, false);
+        assert_eq!(viewport.range(100), 13..63);
+
+        for _ in 0..10 {
+            viewport.pan(100, false);
+        }
+        assert_eq!(viewport.range(100), 0..50);
+
+        for _ in 0..10 {
+            viewport.pan(100, true);
+        }
+        assert_eq!(viewport.range(100), 50..100);
+
+        viewport.reset();
+        assert_eq!(viewport.range(100), 0..100);
+    }
+
+    #[test]
+    fn range_clamps_to_fewer_bars() {
+        let mut viewport = Viewport::default();
+        viewport.zoom_in(100);
+
+        assert_eq!(viewport.range(60), 0..50);
+        assert_eq!(viewport.range(30), 0..30);
+        assert_eq!(viewport.range(0), 0..0);
+
+        viewport.pan(30, true);
+        assert_eq!(viewport.range(100), 50..100);
+
+        viewport.zoom_out(30);
+        assert!(!viewport.is_zoomed());
+    }
+}
@@ -0,0 +1,419 @@
+use crate::{app::GraphType, provider::Provider, utils::parse_interval};
+use std::{fmt, str::FromStr};
+use yahoo_finance::Interval;
+
+pub const USAGE: &str = "\
+tuinance - financial data in your terminal
+
+USAGE:
+    tuinance [OPTIONS] [TICKERS]...
+    tuinance <COMMAND> [ARGS]...
+
+ARGS:
+    <TICKERS>...              Symbols to display instead of the configured watchlist
+
+OPTIONS:
+    -c, --config <PATH>       Use the configuration file at PATH
+    -i, --interval <RANGE>    Initial interval (5d, 1mo, 3mo, 6mo, 1y, 2y, 5y, 10y, ytd, max)
+    -r, --range <RANGE>       Same as --interval
+    -g, --graph <TYPE>        Initial graph type (price, volume, portfolio)
+    -f, --fullscreen          Start with the chart in fullscreen
+    -p, --provider <NAME>     Market data provider (yahoo)
+    -t, --theme <NAME>        Color theme (default, light, mono)
+    -o, --format <FORMAT>     Output format of the headless commands (table, json, jsonl, csv,
+                              parquet for history)
+        --jsonl               Same as --format jsonl
+        --output <PATH>       Write the output of the history or snapshot command to PATH instead of stdout
+        --benchmark <SYMBOL>  Benchmark of the risk command instead of the configured one
+        --size <COLSxROWS>    Terminal size the snapshot command renders at (default 120x40)
+        --init-config         Write an example configuration file and exit
+    -h, --help                Print this help and exit
+    -V, --version             Print the version and exit
+
+COMMANDS:
+    config check              Validate the configuration file without starting the TUI
+    config path               Print the resolved configuration file path
+    config init               Write an example configuration file
+    quote <SYMBOLS>...        Print the last price, change and volume of each symbol
+    watch <SYMBOLS>...        Print realtime quotes as they are streamed, one per line
+    history <SYMBOL>          Print the daily bars of SYMBOL over --range (csv by default)
+    risk [SYMBOLS]...         Print beta, alpha, correlation, Sharpe and Sortino over --range
+                              (the watchlist and the portfolio when no symbols are given)
+    snapshot <SYMBOL>         Render the chart of SYMBOL to an .svg, .ansi, .txt or .png file
+                              (ANSI on stdout)
+    help                      Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Tui,
+    Help,
+    Version,
+    ConfigCheck,
+    ConfigPath,
+    ConfigInit,
+    Quote,
+    Watch,
+    History,
+    Snapshot,
+    Risk,
+}
+
+/// Text output format of the headless commands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Table,
+    Json,
+    /// One JSON object per line.
+    Jsonl,
+    Csv,
+}
+
+/// Format given with `--format`: a text format, or a Parquet file, which
+/// only `history` writes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Output {
+    Text(Format),
+    Parquet,
+}
+
+impl FromStr for Output {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "parquet" => Ok(Output::Parquet),
+            s => s.parse().map(Output::Text),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "jsonl" | "ndjson" => Ok(Format::Jsonl),
+            "csv" => Ok(Format::Csv),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\n\nRun `tuinance --help` for usage.", self.0)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[derive(Debug, Clone)]
+pub struct Cli {
+    pub command: Command,
+    pub config: Option<String>,
+    pub tickers: Vec<String>,
+    pub interval: Option<Interval>,
+    pub graph: Option<GraphType>,
+    pub fullscreen: bool,
+    pub provider: Option<Provider>,
+    pub theme: Option<String>,
+    pub format: Option<Output>,
+    pub output: Option<String>,
+    /// Columns and rows of the snapshot.
+    pub size: Option<(u16, u16)>,
+    pub benchmark: Option<String>,
+}
+
+impl Cli {
+    /// The text format given with `--format`; Parquet is only accepted for
+    /// `history`, which reads `format` itself.
+    pub fn text_format(&self) -> Option<Format> {
+        match self.format {
+            Some(Output::Text(format)) => Some(format),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Result<Self, CliError> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse<I>(args: I) -> Result<Self, CliError>
+    where
+        I: IntoIterator<Item = String>
+    {
+        let mut cli = Cli {
+            command: Command::Tui,
+            config: None,
+            tickers: vec![],
+            interval: None,
+            graph: None,
+            fullscreen: false,
+            provider: None,
+            theme: None,
+            format: None,
+            output: None,
+            size: None,
+            benchmark: None,
+        };
+
+        let mut positional = vec![];
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') || arg == "-" {
+                positional.push(arg);
+                continue;
+            }
+
+            let (flag, inline) = match arg.find('=') {
+                Some(idx) if arg.starts_with("--") => (arg[..idx].to_string(), Some(arg[idx + 1..].to_string())),
+                _ => (arg.clone(), None),
+            };
+
+            let mut value = |name: &str| -> Result<String, CliError> {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| CliError(format!("{} requires a value", name)))
+            };
+
+            match flag.as_str() {
+                "-h" | "--help" => cli.command = Command::Help,
+                "-V" | "--version" => cli.command = Command::Version,
+                "-f" | "--fullscreen" => cli.fullscreen = true,
+                "--init-config" => cli.command = Command::ConfigInit,
+                "--jsonl" => cli.format = Some(Output::Text(Format::Jsonl)),
+                "-c" | "--config" => cli.config = Some(value("--config")?),
+                "-t" | "--theme" => cli.theme = Some(value("--theme")?),
+                "--output" => cli.output = Some(value("--output")?),
+                "--benchmark" => cli.benchmark = Some(value("--benchmark")?.to_uppercase()),
+                "--size" => {
+                    let val = value("--size")?;
+                    cli.size = Some(parse_size(&val)
+                        .ok_or_else(|| CliError(format!("invalid size: {} (expected e.g. 120x40)", val)))?);
+                }
+                "-i" | "--interval" | "-r" | "--range" => {
+                    let val = value(&flag)?;
+                    cli.interval = Some(parse_interval(&val)
+                        .ok_or_else(|| CliError(format!("invalid interval: {}", val)))?);
+                }
+                "-g" | "--graph" => {
+                    let val = value("--graph")?;
+                    cli.graph = Some(val.parse()
+                        .map_err(|_| CliError(format!("invalid graph type: {}", val)))?);
+                }
+                "-o" | "--format" => {
+                    let val = value("--format")?;
+
+                    cli.format = Some(val.parse()
+                        .map_err(|_| CliError(format!("invalid format: {}", val)))?);
+                }
+                "-p" | "--provider" => {
+                    let val = value("--provider")?;
+                    cli.provider = Some(val.parse()
+                        .map_err(|_| CliError(format!("unknown provider: {}", val)))?);
+                }
+                _ => return Err(CliError(format!("unknown option: {}", arg))),
+            }
+        }
+
+        // help and version win over everything else
+        if cli.command != Command::Tui {
+            return Ok(cli);
+        }
+
+        let mut positional = positional.into_iter();
+
+        match positional.next() {
+            Some(arg) if arg == "help" => cli.command = Command::Help,
+            Some(arg) if arg == "config" => {
+                cli.command = match positional.next().as_deref() {
+                    Some("check") => Command::ConfigCheck,
+                    Some("path") => Command::ConfigPath,
+                    Some("init") => Command::ConfigInit,
+                    Some(other) => return Err(CliError(format!("unknown config command: {}", other))),
+                    None => return Err(CliError("config requires a command (check, path, init)".into())),
+                };
+
+                if let Some(extra) = positional.next() {
+                    return Err(CliError(format!("unexpected argument: {}", extra)));
+                }
+            }
+            Some(arg) if arg == "quote" || arg == "watch" => {
+                cli.command = match arg.as_str() {
+                    "quote" => Command::Quote,
+                    _ => Command::Watch,
+                };
+
+                cli.tickers.extend(positional.map(|t| t.to_uppercase()));
+
+                if cli.tickers.is_empty() {
+                    return Err(CliError(format!("{} requires at least one symbol", arg)));
+                }
+            }
+            Some(arg) if arg == "risk" => {
+                cli.command = Command::Risk;
+                cli.tickers.extend(positional.map(|t| t.to_uppercase()));
+            }
+            Some(arg) if arg == "history" || arg == "snapshot" => {
+                cli.command = match arg.as_str() {
+                    "history" => Command::History,
+                    _ => Command::Snapshot,
+                };
+
+                match (positional.next(), positional.next()) {
+                    (Some(symbol), None) => cli.tickers.push(symbol.to_uppercase()),
+                    (None, _) => return Err(CliError(format!("{} requires a symbol", arg))),
+                    (Some(_), Some(extra)) => return Err(CliError(format!("unexpected argument: {}", extra))),
+                }
+            }
+            Some(arg) => {
+                cli.tickers.push(arg.to_uppercase());
+                cli.tickers.extend(positional.map(|t| t.to_uppercase()));
+            }
+            None => (),
+        }
+
+        if cli.format == Some(Output::Parquet) {
+            match (cli.command, cfg!(feature = "parquet")) {
+                (Command::History, true) => (),
+                (Command::History, false) => return Err(CliError(
+                    "parquet output requires tuinance built with `--features parquet`".into()
+                )),
+                _ => return Err(CliError("parquet output is only supported by the history command".into())),
+            }
+        }
+
+        Ok(cli)
+    }
+}
+
+/// Parses `COLSxROWS`, e.g. `120x40`.
+fn parse_size(s: &str) -> Option<(u16, u16)> {
+    let (cols, rows) = s.split_once(['x', 'X'])?;
+    let size = (cols.trim().parse().ok()?, rows.trim().parse().ok()?);
+
+    match size {
+        (cols, rows) if cols >= 20 && rows >= 5 => Some((cols, rows)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size() {
+        assert_eq!(parse_size("120x40"), Some((120, 40)));
+        assert_eq!(parse_size("80X24"), Some((80, 24)));
+        assert_eq!(parse_size(" 20 x 5 "), Some((20, 5)));
+        assert_eq!(parse_size("19x5"), None);
+        assert_eq!(parse_size("20x4"), None);
+        assert_eq!(parse_size("120"), None);
+        assert_eq!(parse_size("120x"), None);
+        assert_eq!(parse_size("-1x40"), None);
+        assert_eq!(parse_size("70000x40"), None);
+    }
+
+    fn parse(args: &str) -> Result<Cli, String> {
+        Cli::parse(args.split_whitespace().map(String::from)).map_err(|e| e.0)
+    }
+
+    #[test]
+    fn flags() {
+        let cli = parse("-c conf.toml --theme=light -i 1y -g volume -f -p yahoo --jsonl aapl msft").unwrap();
+
+        assert_eq!(cli.command, Command::Tui);
+        assert_eq!(cli.config.as_deref(), Some("conf.toml"));
+        assert_eq!(cli.theme.as_deref(), Some("light"));
+        assert_eq!(cli.interval.map(|i| i.to_string()), Some(Interval::_1y.to_string()));
+        assert!(matches!(cli.graph, Some(GraphType::Volume)));
+        assert!(cli.fullscreen);
+        assert_eq!(cli.provider, Some(Provider::Yahoo));
+        assert_eq!(cli.text_format(), Some(Format::Jsonl));
+        assert_eq!(cli.tickers, vec!["AAPL", "MSFT"]);
+
+        let cli = parse("").unwrap();
+        assert_eq!(cli.command, Command::Tui);
+        assert!(cli.tickers.is_empty() && cli.format.is_none() && !cli.fullscreen);
+    }
+
+    #[test]
+    fn invalid_flags() {
+        assert_eq!(parse("--verbose").unwrap_err(), "unknown option: --verbose");
+        assert_eq!(parse("aapl -x").unwrap_err(), "unknown option: -x");
+        assert_eq!(parse("--theme").unwrap_err(), "--theme requires a value");
+        assert_eq!(parse("-r").unwrap_err(), "-r requires a value");
+        assert_eq!(parse("--interval 7d").unwrap_err(), "invalid interval: 7d");
+        assert_eq!(parse("--format xml").unwrap_err(), "invalid format: xml");
+        assert_eq!(parse("--graph pie").unwrap_err(), "invalid graph type: pie");
+        assert_eq!(parse("snapshot aapl --size 10x10").unwrap_err(), "invalid size: 10x10 (expected e.g. 120x40)");
+    }
+
+    #[test]
+    fn help_and_version_win() {
+        assert_eq!(parse("quote -h").unwrap().command, Command::Help);
+        assert_eq!(parse("history --version").unwrap().command, Command::Version);
+        assert_eq!(parse("help").unwrap().command, Command::Help);
+        assert_eq!(parse("--init-config").unwrap().command, Command::ConfigInit);
+    }
+
+    #[test]
+    fn subcommands() {
+        assert_eq!(parse("config check").unwrap().command, Command::ConfigCheck);
+        assert_eq!(parse("config path").unwrap().command, Command::ConfigPath);
+        assert_eq!(parse("config init").unwrap().command, Command::ConfigInit);
+
+        let cli = parse("quote aapl ^gspc --format json").unwrap();
+        assert_eq!(cli.command, Command::Quote);
+        assert_eq!(cli.tickers, vec!["AAPL", "^GSPC"]);
+        assert_eq!(cli.text_format(), Some(Format::Json));
+
+        assert_eq!(parse("watch tsla").unwrap().command, Command::Watch);
+
+        let cli = parse("risk --benchmark spy").unwrap();
+        assert_eq!(cli.command, Command::Risk);
+        assert!(cli.tickers.is_empty());
+        assert_eq!(cli.benchmark.as_deref(), Some("SPY"));
+
+        let cli = parse("history msft -r 5y --output msft.csv").unwrap();
+        assert_eq!(cli.command, Command::History);
+        assert_eq!(cli.tickers, vec!["MSFT"]);
+        assert_eq!(cli.output.as_deref(), Some("msft.csv"));
+
+        let cli = parse("snapshot aapl --size 100x30").unwrap();
+        assert_eq!(cli.command, Command::Snapshot);
+        assert_eq!(cli.size, Some((100, 30)));
+    }
+
+    #[test]
+    fn invalid_subcommands() {
+        assert_eq!(parse("config").unwrap_err(), "config requires a command (check, path, init)");
+        assert_eq!(parse("config edit").unwrap_err(), "unknown config command: edit");
+        assert_eq!(parse("config check now").unwrap_err(), "unexpected argument: now");
+        assert_eq!(parse("quote").unwrap_err(), "quote requires at least one symbol");
+        assert_eq!(parse("history").unwrap_err(), "history requires a symbol");
+        assert_eq!(parse("snapshot aapl msft").unwrap_err(), "unexpected argument: msft");
+    }
+
+    #[test]
+    fn parquet_only_for_history() {
+        assert_eq!(
+            parse("quote aapl --format parquet").unwrap_err(),
+            "parquet output is only supported by the history command",
+        );
+
+        match cfg!(feature = "parquet") {
+            true => assert_eq!(parse("history aapl -o parquet").unwrap().format, Some(Output::Parquet)),
+            false => assert!(parse("history aapl -o parquet").unwrap_err().contains("--features parquet")),
+        }
+    }
+}
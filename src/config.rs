@@ -1,40 +1,714 @@
+use crate::{
+    action::{self, ActionConfig},
+    alert::{AlertConfig, Condition},
+    allocation::Grouping,
+    ledger::LotMethod,
+    portfolio::PortfolioConfig,
+    ui::theme::{parse_color, Theme},
+    utils::parse_interval,
+    watchlist::{self, Column},
+};
 use serde::Deserialize;
-use std::fs;
+use std::{
+    collections::HashMap,
+    env,
+    fmt,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use yahoo_finance::Interval;
+
+pub const FILE_NAME: &str = "tuinance.toml";
+pub const EXAMPLE: &str = include_str!("../config-example.toml");
+
+const KEYS: [&str; 10] = [
+    "tickers", "columns", "interval", "theme", "colors", "portfolio", "alerts", "actions", "benchmark", "risk_free_rate",
+];
+const COLOR_KEYS: [&str; 10] = ["border", "title", "label", "text", "price", "volume", "selected", "up", "down", "alert"];
+const PORTFOLIO_KEYS: [&str; 6] = ["file", "ledger", "lots", "positions", "tags", "targets"];
+const POSITION_KEYS: [&str; 4] = ["symbol", "shares", "cost", "currency"];
+const ALERT_KEYS: [&str; 4] = ["symbol", "when", "command", "webhook"];
+const ACTION_KEYS: [&str; 4] = ["command", "webhook", "cooldown", "rate_limit"];
+
+/// A single problem found in a configuration file.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub key: String,
+    pub message: String,
+    /// 1-based line and column, when the offending text could be located.
+    pub location: Option<(usize, usize)>,
+}
+
+impl Diagnostic {
+    fn new(key: &str, message: String, location: Option<(usize, usize)>) -> Self {
+        Self {
+            key: key.to_string(),
+            message,
+            location,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}`: {}", self.key, self.message)?;
+
+        if let Some((line, column)) = self.location {
+            write!(f, " (line {}, column {})", line, column)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        message: String,
+        location: Option<(usize, usize)>,
+    },
+    Invalid {
+        errors: Vec<Diagnostic>,
+        warnings: Vec<Diagnostic>,
+    },
+}
+
+impl ConfigError {
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ConfigError::Io { source, .. } if source.kind() == io::ErrorKind::NotFound)
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => write!(f, "could not read {}: {}", path.display(), source),
+            ConfigError::Parse { message, location: Some((line, column)) } => {
+                write!(f, "invalid TOML at line {}, column {}: {}", line, column, message)
+            }
+            ConfigError::Parse { message, location: None } => write!(f, "invalid TOML: {}", message),
+            ConfigError::Invalid { errors, .. } => {
+                write!(f, "invalid configuration:")?;
+
+                for problem in errors {
+                    write!(f, "\n  {}", problem)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
 
 #[derive(Deserialize)]
 pub struct Config {
-    tickers: Vec<String>
+    #[serde(default = "default_tickers")]
+    tickers: Vec<String>,
+    #[serde(default)]
+    columns: Option<Vec<String>>,
+    #[serde(default)]
+    interval: Option<String>,
+    #[serde(default)]
+    theme: Option<String>,
+    #[serde(default)]
+    colors: HashMap<String, String>,
+    #[serde(default)]
+    portfolio: PortfolioConfig,
+    #[serde(default)]
+    alerts: Vec<AlertConfig>,
+    #[serde(default)]
+    actions: ActionConfig,
+    #[serde(default)]
+    benchmark: Option<String>,
+    #[serde(default)]
+    risk_free_rate: f64,
+}
+
+fn default_tickers() -> Vec<String> {
+    vec!["MSFT".into()]
 }
 
 impl Config {
     pub fn default() -> Self {
         Self {
-            tickers: vec!["MSFT".into()]
+            tickers: default_tickers(),
+            columns: None,
+            interval: None,
+            theme: None,
+            colors: HashMap::new(),
+            portfolio: PortfolioConfig::default(),
+            alerts: vec![],
+            actions: ActionConfig::default(),
+            benchmark: None,
+            risk_free_rate: 0.0,
         }
     }
 
     pub fn tickers(&self) -> Vec<&str> {
         self.tickers.iter().map(|elem| elem.as_str()).collect::<Vec<&str>>().clone()
     }
-    
-    pub fn read(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let contents = fs::read_to_string(path)?;
 
-        match toml::from_str(&contents) {
-            Ok(val) => Ok(val),
-            Err(_) => Ok(Self::default())
+    /// Columns of the watchlist table, skipping unknown names.
+    pub fn columns(&self) -> Vec<Column> {
+        match &self.columns {
+            Some(columns) => columns.iter().filter_map(|c| c.parse().ok()).collect(),
+            None => watchlist::default_columns(),
         }
     }
+
+    pub fn interval(&self) -> Option<Interval> {
+        self.interval.as_deref().and_then(parse_interval)
+    }
+
+    pub fn portfolio(&self) -> &PortfolioConfig {
+        &self.portfolio
+    }
+
+    pub fn alerts(&self) -> &Vec<AlertConfig> {
+        &self.alerts
+    }
+
+    pub fn actions(&self) -> &ActionConfig {
+        &self.actions
+    }
+
+    /// Symbol the risk metrics are measured against, such as `SPY`.
+    pub fn benchmark(&self) -> Option<&str> {
+        self.benchmark.as_deref()
+    }
+
+    /// Annual risk-free rate, as a fraction.
+    pub fn risk_free_rate(&self) -> f64 {
+        self.risk_free_rate
+    }
+
+    pub fn theme_name(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    /// Builds the theme named `name` (or the configured one) with the
+    /// `[colors]` overrides applied on top.
+    pub fn theme(&self, name: Option<&str>) -> Option<Theme> {
+        let mut theme = match name.or_else(|| self.theme_name()) {
+            Some(name) => Theme::by_name(name)?,
+            None => Theme::default(),
+        };
+
+        for (key, value) in self.colors.iter() {
+            if let Some(color) = parse_color(value) {
+                theme.set(key, color);
+            }
+        }
+
+        Some(theme)
+    }
+
+    /// Parses and validates a configuration, returning it together with any
+    /// non-fatal warnings such as unknown keys.
+    pub fn parse(contents: &str) -> Result<(Self, Vec<Diagnostic>), ConfigError> {
+        let value: toml::Value = toml::from_str(contents).map_err(parse_error)?;
+        let config: Self = toml::from_str(contents).map_err(parse_error)?;
+
+        let warnings = unknown_keys(contents, &value);
+        let errors = config.validate(contents);
+
+        match errors.is_empty() {
+            true => Ok((config, warnings)),
+            false => Err(ConfigError::Invalid { errors, warnings }),
+        }
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<Diagnostic>), ConfigError> {
+        let path = path.as_ref();
+
+        let contents = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Self::parse(&contents)
+    }
+
+    /// Reads the configuration from the resolved path. A missing file is only
+    /// an error when its location was given explicitly, otherwise the defaults
+    /// are used.
+    pub fn load(flag: Option<&str>) -> Result<(Self, Vec<Diagnostic>), ConfigError> {
+        let explicit = Self::is_explicit(flag);
+
+        match Self::path(flag) {
+            Some(path) => match Self::read(&path) {
+                Err(e) if !explicit && e.is_not_found() => Ok((Self::default(), vec![])),
+                res => res,
+            },
+            None => Ok((Self::default(), vec![])),
+        }
+    }
+
+    fn validate(&self, contents: &str) -> Vec<Diagnostic> {
+        let mut errors = vec![];
+
+        if self.tickers.is_empty() {
+            errors.push(Diagnostic::new("tickers", "must contain at least one symbol".into(), locate(contents, "tickers")));
+        }
+
+        for (idx, symbol) in self.tickers.iter().enumerate() {
+            if !is_valid_symbol(symbol) {
+                errors.push(Diagnostic::new(
+                    &format!("tickers[{}]", idx),
+                    format!("invalid symbol \"{}\"", symbol),
+                    locate(contents, &format!("\"{}\"", symbol)),
+                ));
+            } else if self.tickers[..idx].iter().any(|s| s.eq_ignore_ascii_case(symbol)) {
+                errors.push(Diagnostic::new(
+                    &format!("tickers[{}]", idx),
+                    format!("duplicate symbol \"{}\"", symbol),
+                    locate(contents, &format!("\"{}\"", symbol)),
+                ));
+            }
+        }
+
+        if let Some(columns) = &self.columns {
+            if columns.is_empty() {
+                errors.push(Diagnostic::new("columns", "must contain at least one column".into(), locate(contents, "columns")));
+            }
+
+            for (idx, column) in columns.iter().enumerate() {
+                if column.parse::<Column>().is_err() {
+                    errors.push(Diagnostic::new(
+                        &format!("columns[{}]", idx),
+                        format!("unknown column \"{}\" (expected one of {})", column, Column::NAMES.join(", ")),
+                        locate(contents, &format!("\"{}\"", column)),
+                    ));
+                }
+            }
+        }
+
+        if let Some(interval) = &self.interval {
+            if parse_interval(interval).is_none() {
+                errors.push(Diagnostic::new(
+                    "interval",
+                    format!("invalid interval \"{}\" (expected one of 5d, 1mo, 3mo, 6mo, 1y, 2y, 5y, 10y, ytd, max)", interval),
+                    locate(contents, "interval"),
+                ));
+            }
+        }
+
+        if let Some(benchmark) = self.benchmark.as_deref().filter(|b| !is_valid_symbol(b)) {
+            errors.push(Diagnostic::new(
+                "benchmark",
+                format!("invalid symbol \"{}\"", benchmark),
+                locate(contents, "benchmark"),
+            ));
+        }
+
+        if !self.risk_free_rate.is_finite() || self.risk_free_rate.abs() >= 1.0 {
+            errors.push(Diagnostic::new(
+                "risk_free_rate",
+                "must be an annual rate as a fraction, e.g. 0.04 for 4%".into(),
+                locate(contents, "risk_free_rate"),
+            ));
+        }
+
+        if let Some(theme) = &self.theme {
+            if Theme::by_name(theme).is_none() {
+                errors.push(Diagnostic::new(
+                    "theme",
+                    format!("unknown theme \"{}\" (available: {})", theme, Theme::NAMES.join(", ")),
+                    locate(contents, "theme"),
+                ));
+            }
+        }
+
+        let mut colors: Vec<(&String, &String)> = self.colors.iter().collect();
+        colors.sort();
+
+        for (key, value) in colors {
+            if COLOR_KEYS.contains(&key.as_str()) && parse_color(value).is_none() {
+                errors.push(Diagnostic::new(
+                    &format!("colors.{}", key),
+                    format!("invalid color \"{}\" (expected a color name or #rrggbb)", value),
+                    locate(contents, &format!("\"{}\"", value)),
+                ));
+            }
+        }
+
+        if let Some(lots) = self.portfolio.lots() {
+            if lots.parse::<LotMethod>().is_err() {
+                errors.push(Diagnostic::new(
+                    "portfolio.lots",
+                    format!("unknown lot method \"{}\" (expected fifo, lifo or average)", lots),
+                    locate(contents, "lots"),
+                ));
+            }
+        }
+
+        let mut groupings: Vec<(&String, &HashMap<String, f64>)> = self.portfolio.targets().iter().collect();
+        groupings.sort_by_key(|(k, _)| k.as_str());
+
+        for (grouping, targets) in groupings {
+            let key = format!("portfolio.targets.{}", grouping);
+
+            if grouping.parse::<Grouping>().is_err() {
+                errors.push(Diagnostic::new(
+                    &key,
+                    "unknown grouping (expected sector, industry, type, currency or tag)".into(),
+                    locate(contents, grouping),
+                ));
+                continue;
+            }
+
+            if targets.values().any(|t| !t.is_finite() || *t < 0.0 || *t > 100.0) {
+                errors.push(Diagnostic::new(&key, "targets must be percentages between 0 and 100".into(), locate(contents, grouping)));
+            } else if targets.values().sum::<f64>() > 100.0 + 1e-6 {
+                errors.push(Diagnostic::new(&key, "targets add up to more than 100%".into(), locate(contents, grouping)));
+            }
+        }
+
+        for (idx, alert) in self.alerts.iter().enumerate() {
+            let key = format!("alerts[{}]", idx);
+
+            if !is_valid_symbol(alert.symbol()) {
+                errors.push(Diagnostic::new(
+                    &key,
+                    format!("invalid symbol \"{}\"", alert.symbol()),
+                    locate(contents, &format!("\"{}\"", alert.symbol())),
+                ));
+            }
+
+            if let Err(e) = alert.when().parse::<Condition>() {
+                errors.push(Diagnostic::new(&key, e, locate(contents, &format!("\"{}\"", alert.when()))));
+            }
+
+            if let Some(webhook) = alert.webhook().filter(|w| !action::is_valid_webhook(w)) {
+                errors.push(Diagnostic::new(
+                    &format!("{}.webhook", key),
+                    format!("invalid URL \"{}\" (expected http:// or https://)", webhook),
+                    locate(contents, &format!("\"{}\"", webhook)),
+                ));
+            }
+        }
+
+        if let Some(webhook) = self.actions.webhook().filter(|w| !action::is_valid_webhook(w)) {
+            errors.push(Diagnostic::new(
+                "actions.webhook",
+                format!("invalid URL \"{}\" (expected http:// or https://)", webhook),
+                locate(contents, &format!("\"{}\"", webhook)),
+            ));
+        }
+
+        if self.actions.rate_limit() == 0 {
+            errors.push(Diagnostic::new(
+                "actions.rate_limit",
+                "must be at least 1 action per minute".into(),
+                locate(contents, "rate_limit"),
+            ));
+        }
+
+        for (idx, position) in self.portfolio.positions().iter().enumerate() {
+            let key = format!("portfolio.positions[{}]", idx);
+
+            for (field, message) in position.problems() {
+                let text = match field {
+                    "currency" => position.currency(),
+                    _ => position.symbol(),
+                };
+
+                errors.push(Diagnostic::new(&key, message, locate(contents, &format!("\"{}\"", text))));
+            }
+        }
+
+        errors
+    }
+
+    /// Whether the configuration location was chosen by the user rather than
+    /// derived from the XDG defaults.
+    pub fn is_explicit(flag: Option<&str>) -> bool {
+        flag.is_some() || non_empty_var("TUINANCE_CONFIG").is_some()
+    }
+
+    /// Resolves the configuration file location.
+    ///
+    /// An explicit `flag` path wins, followed by `TUINANCE_CONFIG`. Otherwise the
+    /// first existing file of `$XDG_CONFIG_HOME/tuinance.toml` and
+    /// `$HOME/.config/tuinance.toml` is used, or the first of those candidates
+    /// if neither exists yet.
+    pub fn path(flag: Option<&str>) -> Option<PathBuf> {
+        if let Some(path) = flag {
+            return Some(PathBuf::from(path));
+        }
+
+        if let Some(path) = non_empty_var("TUINANCE_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+
+        let candidates = default_paths();
+
+        candidates
+            .iter()
+            .find(|path| path.is_file())
+            .or_else(|| candidates.first())
+            .cloned()
+    }
+
+    /// Writes the commented example configuration to `path`, creating parent
+    /// directories as needed. Existing files are never overwritten.
+    pub fn init<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+
+        if path.exists() {
+            return Err(format!("{} already exists", path.display()).into());
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, EXAMPLE)?;
+        Ok(())
+    }
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).map(|(config, _)| config)
+    }
+}
+
+/// Symbols are letters, digits and the punctuation Yahoo uses for indices,
+/// share classes, currencies and futures (`^GSPC`, `BRK-B`, `EURUSD=X`).
+pub fn is_valid_symbol(symbol: &str) -> bool {
+    !symbol.is_empty()
+        && symbol.len() <= 16
+        && symbol.chars().all(|c| c.is_ascii_alphanumeric() || "^.-=".contains(c))
+}
+
+fn parse_error(e: toml::de::Error) -> ConfigError {
+    let mut message = e.to_string();
+
+    if let Some(idx) = message.rfind(" at line ") {
+        message.truncate(idx);
+    }
+
+    let location = e.line_col().map(|(line, col)| (line + 1, col + 1));
+
+    ConfigError::Parse { message, location }
+}
+
+fn unknown_keys(contents: &str, value: &toml::Value) -> Vec<Diagnostic> {
+    let mut warnings = vec![];
+
+    let table = match value.as_table() {
+        Some(table) => table,
+        None => return warnings,
+    };
+
+    for (key, value) in table.iter() {
+        if !KEYS.contains(&key.as_str()) {
+            warnings.push(Diagnostic::new(key, "unknown key".into(), locate(contents, key)));
+        }
+
+        if let ("colors", Some(colors)) = (key.as_str(), value.as_table()) {
+            for key in colors.keys().filter(|k| !COLOR_KEYS.contains(&k.as_str())) {
+                warnings.push(Diagnostic::new(
+                    &format!("colors.{}", key),
+                    format!("unknown color (expected one of {})", COLOR_KEYS.join(", ")),
+                    locate(contents, key),
+                ));
+            }
+        }
+
+        if let ("alerts", Some(alerts)) = (key.as_str(), value.as_array()) {
+            for (idx, alert) in alerts.iter().enumerate() {
+                let keys = alert.as_table().into_iter().flat_map(|t| t.keys());
+
+                for key in keys.filter(|k| !ALERT_KEYS.contains(&k.as_str())) {
+                    warnings.push(Diagnostic::new(&format!("alerts[{}].{}", idx, key), "unknown key".into(), locate(contents, key)));
+                }
+            }
+        }
+
+        if let ("actions", Some(actions)) = (key.as_str(), value.as_table()) {
+            for key in actions.keys().filter(|k| !ACTION_KEYS.contains(&k.as_str())) {
+                warnings.push(Diagnostic::new(&format!("actions.{}", key), "unknown key".into(), locate(contents, key)));
+            }
+        }
+
+        if let ("portfolio", Some(portfolio)) = (key.as_str(), value.as_table()) {
+            for key in portfolio.keys().filter(|k| !PORTFOLIO_KEYS.contains(&k.as_str())) {
+                warnings.push(Diagnostic::new(&format!("portfolio.{}", key), "unknown key".into(), locate(contents, key)));
+            }
+
+            let positions = portfolio.get("positions").and_then(|p| p.as_array());
+
+            for (idx, position) in positions.into_iter().flatten().enumerate() {
+                let keys = position.as_table().into_iter().flat_map(|t| t.keys());
+
+                for key in keys.filter(|k| !POSITION_KEYS.contains(&k.as_str())) {
+                    warnings.push(Diagnostic::new(
+                        &format!("portfolio.positions[{}].{}", idx, key),
+                        "unknown key".into(),
+                        locate(contents, key),
+                    ));
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Finds the first occurrence of `needle` outside of comments, returning its
+/// 1-based line and column.
+fn locate(contents: &str, needle: &str) -> Option<(usize, usize)> {
+    contents.lines().enumerate().find_map(|(idx, line)| {
+        let mut quoted = false;
+
+        let end = line
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+
+                c == '#' && !quoted
+            })
+            .map(|(idx, _)| idx)
+            .unwrap_or_else(|| line.len());
+
+        line[..end].find(needle).map(|col| (idx + 1, col + 1))
+    })
+}
+
+fn non_empty_var(key: &str) -> Option<String> {
+    match env::var(key) {
+        Ok(val) if !val.is_empty() => Some(val),
+        _ => None
+    }
+}
+
+fn default_paths() -> Vec<PathBuf> {
+    let mut paths = vec![];
+
+    if let Some(xdg) = non_empty_var("XDG_CONFIG_HOME") {
+        paths.push(PathBuf::from(xdg).join(FILE_NAME));
+    }
+
+    if let Some(home) = non_empty_var("HOME") {
+        let path = PathBuf::from(home).join(".config").join(FILE_NAME);
+
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    paths
 }
 
-impl<T> From<T> for Config 
-where
-    T: Into<String>
-{
-    fn from(data: T) -> Self {
-        match toml::from_str(&data.into()) {
-            Ok(val) => val,
-            Err(_) => Self::default()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(contents: &str) -> Vec<String> {
+        match Config::parse(contents) {
+            Err(ConfigError::Invalid { errors, .. }) => errors.iter().map(|e| e.to_string()).collect(),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => vec![],
         }
     }
+
+    #[test]
+    fn example_is_valid() {
+        let (_, warnings) = Config::parse(EXAMPLE).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn unknown_keys_are_warnings() {
+        let contents = "tickers = [\"AAPL\"]\nrefresh = 5\n\n[colors]\nborder = \"red\"\nshadow = \"red\"\n\n[actions]\nretries = 3\n";
+        let (config, warnings) = Config::parse(contents).unwrap();
+
+        assert_eq!(config.tickers(), vec!["AAPL"]);
+
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings.contains(&"`refresh`: unknown key (line 2, column 1)".to_string()));
+        assert!(warnings.iter().any(|w| w.starts_with("`colors.shadow`: unknown color")));
+        assert!(warnings.contains(&"`actions.retries`: unknown key (line 9, column 1)".to_string()));
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        let contents = [
+            "tickers = [\"AAPL\", \"aapl\", \"BAD SYMBOL\"]",
+            "interval = \"7d\"",
+            "theme = \"neon\"",
+            "risk_free_rate = 4.0",
+            "",
+            "[actions]",
+            "rate_limit = 0",
+        ]
+        .join("\n");
+
+        assert_eq!(errors(&contents), vec![
+            "`tickers[1]`: duplicate symbol \"aapl\" (line 1, column 20)",
+            "`tickers[2]`: invalid symbol \"BAD SYMBOL\" (line 1, column 28)",
+            "`interval`: invalid interval \"7d\" (expected one of 5d, 1mo, 3mo, 6mo, 1y, 2y, 5y, 10y, ytd, max) (line 2, column 1)",
+            "`risk_free_rate`: must be an annual rate as a fraction, e.g. 0.04 for 4% (line 4, column 1)",
+            "`theme`: unknown theme \"neon\" (available: default, light, mono) (line 3, column 1)",
+            "`actions.rate_limit`: must be at least 1 action per minute (line 7, column 1)",
+        ]);
+    }
+
+    #[test]
+    fn invalid_sections_are_errors() {
+        let contents = [
+            "[[alerts]]",
+            "symbol = \"AAPL\"",
+            "when = \"above\"",
+            "webhook = \"ftp://example.com\"",
+            "",
+            "[[portfolio.positions]]",
+            "symbol = \"MSFT\"",
+            "shares = 0",
+            "cost = 10",
+            "",
+            "[portfolio.targets]",
+            "sector = { Technology = 80, Energy = 30 }",
+        ]
+        .join("\n");
+
+        assert_eq!(errors(&contents), vec![
+            "`portfolio.targets.sector`: targets add up to more than 100% (line 12, column 1)",
+            "`alerts[0]`: \"above\" requires a number (line 3, column 8)",
+            "`alerts[0].webhook`: invalid URL \"ftp://example.com\" (expected http:// or https://) (line 4, column 11)",
+            "`portfolio.positions[0]`: shares must be a non-zero number (line 7, column 10)",
+        ]);
+    }
+
+    #[test]
+    fn toml_errors_are_located() {
+        match Config::parse("tickers = [\"AAPL\"\ntheme = ") {
+            Err(ConfigError::Parse { location: Some((line, _)), .. }) => assert!(line >= 1),
+            other => panic!("expected a parse error, got {:?}", other.map(|(_, w)| w)),
+        }
+    }
+
+    #[test]
+    fn theme_falls_back_to_the_configured_one() {
+        let (config, _) = Config::parse("theme = \"mono\"\n\n[colors]\nborder = \"#010203\"").unwrap();
+
+        assert_eq!(config.theme(None).unwrap().border, tui::style::Color::Rgb(1, 2, 3));
+        assert_eq!(config.theme(None).unwrap().price, Theme::mono().price);
+        assert!(config.theme(Some("neon")).is_none());
+    }
 }
@@ -0,0 +1,140 @@
+use crate::{
+    risk::{self, Returns},
+    ticker::Ticker,
+};
+use chrono::NaiveDate;
+
+/// Number of daily returns in each window of the rolling correlation.
+pub const ROLLING_WINDOW: usize = 20;
+
+/// Pairwise correlations of the daily returns of a set of tickers.
+#[derive(Debug, Clone, Default)]
+pub struct Matrix {
+    pub symbols: Vec<String>,
+    returns: Vec<Returns>,
+    /// Row-major, `None` where the pair has too few common dates.
+    values: Vec<Vec<Option<f64>>>,
+}
+
+impl Matrix {
+    pub fn compute(tickers: &[Ticker]) -> Self {
+        let symbols: Vec<String> = tickers.iter().map(|t| t.identifier().clone()).collect();
+        let returns: Vec<Returns> = tickers.iter().map(|t| risk::returns(t.data())).collect();
+
+        let values = (0..returns.len()).map(|row| (0..returns.len()).map(|col| {
+            let pairs: Vec<(f64, f64)> = risk::align(&returns[row], &returns[col])
+                .into_iter()
+                .map(|(_, pair)| pair)
+                .collect();
+
+            risk::correlation(&pairs)
+        }).collect()).collect();
+
+        Self {
+            symbols,
+            returns,
+            values,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        self.values.get(row)?.get(col).copied().flatten()
+    }
+
+    /// Correlation of the pair over a window of `window` days ending on each
+    /// of their common dates.
+    pub fn rolling(&self, row: usize, col: usize, window: usize) -> Vec<(NaiveDate, f64)> {
+        let (a, b) = match (self.returns.get(row), self.returns.get(col)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return vec![],
+        };
+
+        let window = window.max(2);
+        let aligned = risk::align(a, b);
+        let pairs: Vec<(f64, f64)> = aligned.iter().map(|(_, pair)| *pair).collect();
+
+        (window..=pairs.len())
+            .filter_map(|end| risk::correlation(&pairs[end - window..end]).map(|c| (aligned[end - 1].0, c)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticker::Data;
+    use ordered_float::OrderedFloat;
+
+    const RETURNS: [f64; 6] = [0.01, -0.02, 0.03, -0.01, 0.02, 0.015];
+
+    /// A ticker with one daily close per return, starting at 100.
+    fn ticker(symbol: &str, returns: &[f64]) -> Ticker {
+        let mut closes = vec![100.0];
+        for r in returns {
+            closes.push(closes.last().unwrap() * (1.0 + r));
+        }
+
+        let mut ticker = Ticker::new(symbol.into());
+        ticker.set_data(Data::new(
+            closes.iter().map(|c| OrderedFloat::from(*c)).collect(),
+            closes.iter().map(|_| String::new()).collect(),
+            closes.iter().map(|_| 0).collect(),
+            (0..closes.len() as i64).map(|day| day * 86_400).collect(),
+        ));
+        ticker
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn compute_correlates_every_pair() {
+        let inverse: Vec<f64> = RETURNS.iter().map(|r| -r).collect();
+        let matrix = Matrix::compute(&[
+            ticker("A", &RETURNS),
+            ticker("B", &RETURNS),
+            ticker("C", &inverse),
+            ticker("D", &[]),
+        ]);
+
+        assert_eq!(matrix.len(), 4);
+        assert_eq!(matrix.symbols, vec!["A", "B", "C", "D"]);
+        assert!(close(matrix.get(0, 0).unwrap(), 1.0));
+        assert!(close(matrix.get(0, 1).unwrap(), 1.0));
+        assert!(close(matrix.get(0, 2).unwrap(), -1.0));
+        assert!(close(matrix.get(2, 0).unwrap(), -1.0));
+        assert_eq!(matrix.get(0, 3), None);
+        assert_eq!(matrix.get(0, 4), None);
+        assert!(Matrix::compute(&[]).is_empty());
+    }
+
+    #[test]
+    fn rolling_ends_a_window_on_each_common_date() {
+        let matrix = Matrix::compute(&[ticker("A", &RETURNS), ticker("B", &RETURNS[..5])]);
+        let rolling = matrix.rolling(0, 1, 3);
+
+        // five common returns, the first window ending on the third
+        assert_eq!(rolling.len(), 3);
+        assert_eq!(rolling[0].0, NaiveDate::from_ymd_opt(1970, 1, 4).unwrap());
+        assert_eq!(rolling[2].0, NaiveDate::from_ymd_opt(1970, 1, 6).unwrap());
+        assert!(rolling.iter().all(|(_, c)| close(*c, 1.0)));
+    }
+
+    #[test]
+    fn rolling_needs_two_returns_and_known_tickers() {
+        let matrix = Matrix::compute(&[ticker("A", &RETURNS), ticker("B", &RETURNS)]);
+
+        assert_eq!(matrix.rolling(0, 1, 0).len(), RETURNS.len() - 1);
+        assert_eq!(matrix.rolling(0, 1, RETURNS.len() + 1), vec![]);
+        assert_eq!(matrix.rolling(0, 2, 3), vec![]);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{cli::{Format, Output}, quote, ticker::Data, utils::timestamp_to_date};
+use serde::Serialize;
+use std::{fs, io, path::Path};
+
+/// One bar of exported history.
+#[derive(Serialize, Debug, Clone)]
+pub struct Record {
+    /// Seconds since the Unix epoch.
+    pub timestamp: i64,
+    pub date: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// The provider does not return dividend-adjusted closes, so this equals `close`.
+    pub adj_close: f64,
+    pub volume: u64,
+}
+
+pub fn records(data: &Data) -> Vec<Record> {
+    (0..data.len()).map(|idx| {
+        let timestamp = data.timestamps().get(idx).copied().unwrap_or_default();
+        let close = f64::from(data.price_data()[idx]);
+
+        Record {
+            timestamp,
+            date: timestamp_to_date(timestamp).format("%Y-%m-%d").to_string(),
+            open: data.open_data().get(idx).copied().unwrap_or(close),
+            high: data.high_data().get(idx).copied().unwrap_or(close),
+            low: data.low_data().get(idx).copied().unwrap_or(close),
+            close,
+            adj_close: close,
+            volume: data.volume_data().get(idx).copied().unwrap_or(0),
+        }
+    }).collect()
+}
+
+pub fn format(data: &Data, format: Format) -> String {
+    let records = records(data);
+
+    match format {
+        Format::Json => serde_json::to_string_pretty(&records).unwrap_or_default() + "\n",
+        Format::Jsonl => records
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap_or_default() + "\n")
+            .collect(),
+        Format::Csv => {
+            let mut out = String::from("timestamp,date,open,high,low,close,adj_close,volume\n");
+
+            for r in records.iter() {
+                out += &format!(
+                    "{},{},{},{},{},{},{},{}\n",
+                    r.timestamp, r.date, r.open, r.high, r.low, r.close, r.adj_close, r.volume
+                );
+            }
+
+            out
+        }
+        Format::Table => {
+            let rows: Vec<Vec<String>> = records.iter().map(|r| vec![
+                r.date.clone(),
+                format!("{:.2}", r.open),
+                format!("{:.2}", r.high),
+                format!("{:.2}", r.low),
+                format!("{:.2}", r.close),
+                format!("{:.2}", r.adj_close),
+                r.volume.to_string(),
+            ]).collect();
+
+            quote::table(&["DATE", "OPEN", "HIGH", "LOW", "CLOSE", "ADJ CLOSE", "VOLUME"], &rows, 1)
+        }
+    }
+}
+
+/// Writes the formatted bars to `path`, or to stdout when it is `None` or `-`.
+pub fn write(data: &Data, output: Output, path: Option<&Path>) -> io::Result<()> {
+    let contents = match output {
+        Output::Text(format) => self::format(data, format).into_bytes(),
+        Output::Parquet => parquet(&records(data))?,
+    };
+
+    match path.filter(|p| p != &Path::new("-")) {
+        Some(path) => fs::write(path, contents),
+        None => {
+            use std::io::Write;
+
+            let mut stdout = io::stdout();
+            stdout.write_all(&contents)?;
+            stdout.flush()
+        }
+    }
+}
+
+/// File extension of the format, used to name files exported from the TUI.
+pub fn extension(format: Format) -> &'static str {
+    match format {
+        Format::Table => "txt",
+        Format::Json => "json",
+        Format::Jsonl => "jsonl",
+        Format::Csv => "csv",
+    }
+}
+
+/// Encodes the bars as a Parquet file with one row group. The date is a UTF-8
+/// string and the timestamp plain seconds, matching the CSV columns.
+#[cfg(feature = "parquet")]
+fn parquet(records: &[Record]) -> io::Result<Vec<u8>> {
+    use parquet::{
+        data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+    use std::sync::Arc;
+
+    const SCHEMA: &str = "
+        message bar {
+            REQUIRED INT64 timestamp;
+            REQUIRED BYTE_ARRAY date (STRING);
+            REQUIRED DOUBLE open;
+            REQUIRED DOUBLE high;
+            REQUIRED DOUBLE low;
+            REQUIRED DOUBLE close;
+            REQUIRED DOUBLE adj_close;
+            REQUIRED INT64 volume (INTEGER(64, false));
+        }
+    ";
+
+    let encode = || -> parquet::errors::Result<Vec<u8>> {
+        let schema = Arc::new(parse_message_type(SCHEMA)?);
+        let mut writer = SerializedFileWriter::new(Vec::new(), schema, Arc::new(WriterProperties::builder().build()))?;
+        let mut group = writer.next_row_group()?;
+
+        let timestamps: Vec<i64> = records.iter().map(|r| r.timestamp).collect();
+        let dates: Vec<ByteArray> = records.iter().map(|r| ByteArray::from(r.date.as_str())).collect();
+        let prices: Vec<Vec<f64>> = [
+            |r: &Record| r.open,
+            |r: &Record| r.high,
+            |r: &Record| r.low,
+            |r: &Record| r.close,
+            |r: &Record| r.adj_close,
+        ].iter().map(|field| records.iter().map(field).collect()).collect();
+        let volumes: Vec<i64> = records.iter().map(|r| r.volume as i64).collect();
+
+        let mut idx = 0;
+
+        while let Some(mut column) = group.next_column()? {
+            match idx {
+                0 => column.typed::<Int64Type>().write_batch(&timestamps, None, None)?,
+                1 => column.typed::<ByteArrayType>().write_batch(&dates, None, None)?,
+                2..=6 => column.typed::<DoubleType>().write_batch(&prices[idx - 2], None, None)?,
+                _ => column.typed::<Int64Type>().write_batch(&volumes, None, None)?,
+            };
+
+            column.close()?;
+            idx += 1;
+        }
+
+        group.close()?;
+        writer.into_inner()
+    };
+
+    encode().map_err(io::Error::other)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn parquet(_: &[Record]) -> io::Result<Vec<u8>> {
+    Err(io::Error::other("built without parquet support"))
+}
+
+#[cfg(all(test, feature = "parquet"))]
+mod tests {
+    use super::*;
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+    };
+
+    #[test]
+    fn parquet_round_trip() {
+        let records = vec![
+            Record { timestamp: 0, date: "1970-01-01".into(), open: 1.0, high: 2.0, low: 0.5, close: 1.5, adj_close: 1.5, volume: 10 },
+            Record { timestamp: 86_400, date: "1970-01-02".into(), open: 1.5, high: 3.0, low: 1.0, close: 2.5, adj_close: 2.5, volume: 20 },
+        ];
+
+        let path = std::env::temp_dir().join(format!("tuinance-export-{}.parquet", std::process::id()));
+        fs::write(&path, parquet(&records).unwrap()).unwrap();
+        let reader = SerializedFileReader::new(fs::File::open(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().map(|row| row.unwrap()).collect();
+        assert_eq!(rows[1].get_long(0).unwrap(), 86_400);
+        assert_eq!(rows[1].get_string(1).unwrap(), "1970-01-02");
+        assert_eq!(rows[1].get_double(4).unwrap(), 1.0);
+        assert_eq!(rows[1].get_double(5).unwrap(), 2.5);
+        assert_eq!(rows[0].get_ulong(7).unwrap(), 10);
+    }
+}
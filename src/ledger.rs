@@ -0,0 +1,542 @@
+use crate::portfolio::Position;
+use chrono::NaiveDate;
+use std::{fmt, fs, path::Path, str::FromStr};
+
+const EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Buy,
+    Sell,
+    Dividend,
+    Fee,
+    Split,
+}
+
+impl FromStr for Kind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "buy" => Ok(Kind::Buy),
+            "sell" => Ok(Kind::Sell),
+            "dividend" | "div" => Ok(Kind::Dividend),
+            "fee" => Ok(Kind::Fee),
+            "split" => Ok(Kind::Split),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How sold shares are matched against the lots they were bought in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LotMethod {
+    Fifo,
+    Lifo,
+    Average,
+}
+
+impl FromStr for LotMethod {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fifo" => Ok(LotMethod::Fifo),
+            "lifo" => Ok(LotMethod::Lifo),
+            "average" | "avg" => Ok(LotMethod::Average),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for LotMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LotMethod::Fifo => write!(f, "FIFO"),
+            LotMethod::Lifo => write!(f, "LIFO"),
+            LotMethod::Average => write!(f, "Average"),
+        }
+    }
+}
+
+/// One row of the ledger.
+///
+/// * `buy`/`sell`: `quantity` shares at `price`, `fee` added to the cost or
+///   taken from the proceeds.
+/// * `dividend`: `price` per share on `quantity` shares, or on the shares held
+///   at that date when `quantity` is empty.
+/// * `fee`: a standalone charge of `fee` (or `price`).
+/// * `split`: `quantity` new shares for every old one, e.g. `4` or `0.5`.
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub date: NaiveDate,
+    pub kind: Kind,
+    pub symbol: String,
+    pub quantity: Option<f64>,
+    pub price: Option<f64>,
+    pub fee: f64,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lot {
+    pub date: NaiveDate,
+    pub shares: f64,
+    /// Cost per share, including the buy fee.
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Realized {
+    pub date: NaiveDate,
+    pub symbol: String,
+    pub shares: f64,
+    pub proceeds: f64,
+    pub cost: f64,
+}
+
+impl Realized {
+    pub fn gain(&self) -> f64 {
+        self.proceeds - self.cost
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub symbol: String,
+    pub currency: String,
+    pub lots: Vec<Lot>,
+    pub dividends: f64,
+    pub fees: f64,
+}
+
+impl Holding {
+    pub fn shares(&self) -> f64 {
+        self.lots.iter().map(|l| l.shares).sum()
+    }
+
+    pub fn cost_basis(&self) -> f64 {
+        self.lots.iter().map(|l| l.shares * l.cost).sum()
+    }
+}
+
+/// Positions and realized results derived from a ledger.
+#[derive(Debug, Clone)]
+pub struct Book {
+    pub method: LotMethod,
+    /// Every symbol that appears in the ledger, including closed positions.
+    pub holdings: Vec<Holding>,
+    pub realized: Vec<Realized>,
+}
+
+impl Book {
+    pub fn new(method: LotMethod) -> Self {
+        Self {
+            method,
+            holdings: vec![],
+            realized: vec![],
+        }
+    }
+
+    /// Applies one transaction on top of the ones applied before, which are
+    /// expected to be dated no later.
+    pub fn apply(&mut self, t: &Transaction) -> Result<(), String> {
+        let idx = match self.holdings.iter().position(|h| h.symbol == t.symbol) {
+            Some(idx) => idx,
+            None => {
+                self.holdings.push(Holding {
+                    symbol: t.symbol.clone(),
+                    currency: t.currency.clone(),
+                    lots: vec![],
+                    dividends: 0.0,
+                    fees: 0.0,
+                });
+                self.holdings.len() - 1
+            }
+        };
+
+        let holding = &mut self.holdings[idx];
+        let quantity = t.quantity.unwrap_or(0.0);
+        let price = t.price.unwrap_or(0.0);
+
+        match t.kind {
+            Kind::Buy => {
+                let lot = Lot {
+                    date: t.date,
+                    shares: quantity,
+                    cost: (quantity * price + t.fee) / quantity,
+                };
+
+                match (self.method, holding.lots.first_mut()) {
+                    (LotMethod::Average, Some(pooled)) => {
+                        let shares = pooled.shares + lot.shares;
+                        pooled.cost = (pooled.shares * pooled.cost + lot.shares * lot.cost) / shares;
+                        pooled.shares = shares;
+                    }
+                    _ => holding.lots.push(lot),
+                }
+            }
+            Kind::Sell => {
+                if quantity > holding.shares() + EPSILON {
+                    return Err(format!(
+                        "{}: selling {} {} but only {:.4} held",
+                        t.date, quantity, t.symbol, holding.shares()
+                    ));
+                }
+
+                let mut remaining = quantity;
+                let mut cost = 0.0;
+
+                while remaining > EPSILON {
+                    let lot = match self.method {
+                        LotMethod::Lifo => holding.lots.last_mut(),
+                        _ => holding.lots.first_mut(),
+                    };
+
+                    let lot = match lot {
+                        Some(lot) => lot,
+                        None => break,
+                    };
+
+                    let matched = remaining.min(lot.shares);
+                    cost += matched * lot.cost;
+                    lot.shares -= matched;
+                    remaining -= matched;
+
+                    if lot.shares <= EPSILON {
+                        match self.method {
+                            LotMethod::Lifo => holding.lots.pop(),
+                            _ => Some(holding.lots.remove(0)),
+                        };
+                    }
+                }
+
+                self.realized.push(Realized {
+                    date: t.date,
+                    symbol: t.symbol.clone(),
+                    shares: quantity,
+                    proceeds: quantity * price - t.fee,
+                    cost,
+                });
+            }
+            Kind::Dividend => {
+                let shares = t.quantity.unwrap_or_else(|| holding.shares());
+                holding.dividends += shares * price - t.fee;
+            }
+            Kind::Fee => holding.fees += match t.fee == 0.0 {
+                true => price,
+                false => t.fee,
+            },
+            Kind::Split => {
+                for lot in holding.lots.iter_mut() {
+                    lot.shares *= quantity;
+                    lot.cost /= quantity;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn holding(&self, symbol: &str) -> Option<&Holding> {
+        self.holdings.iter().find(|h| h.symbol == symbol)
+    }
+
+    pub fn realized_gain(&self, symbol: &str) -> f64 {
+        self.realized.iter().filter(|r| r.symbol == symbol).map(|r| r.gain()).sum()
+    }
+
+    /// Open positions with their average cost per share.
+    pub fn positions(&self) -> Vec<Position> {
+        self.holdings
+            .iter()
+            .filter(|h| h.shares().abs() > EPSILON)
+            .map(|h| Position::new(h.symbol.clone(), h.shares(), h.cost_basis() / h.shares(), h.currency.clone()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    transactions: Vec<Transaction>,
+}
+
+impl Ledger {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("could not read ledger {}: {}", path.display(), e))?;
+
+        Self::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Parses a CSV ledger. The header names the columns, in any order:
+    /// `date,type,symbol,quantity,price,fee,currency`; `fee` and `currency`
+    /// may be omitted.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+
+        let header: Vec<String> = match lines.next() {
+            Some((_, line)) => split_csv(line).iter().map(|h| h.to_lowercase()).collect(),
+            None => return Ok(Self::default()),
+        };
+
+        let column = |name: &str| header.iter().position(|h| h == name);
+        let required = |name: &str| column(name).ok_or_else(|| format!("missing \"{}\" column", name));
+
+        let date_col = required("date")?;
+        let kind_col = required("type")?;
+        let symbol_col = required("symbol")?;
+        let quantity_col = required("quantity")?;
+        let price_col = required("price")?;
+        let fee_col = column("fee");
+        let currency_col = column("currency");
+
+        let mut transactions = vec![];
+
+        for (idx, line) in lines {
+            let fields = split_csv(line);
+            let field = |col: Option<usize>| col.and_then(|c| fields.get(c)).map(|f| f.trim()).filter(|f| !f.is_empty());
+            let err = |message: String| format!("line {}: {}", idx + 1, message);
+
+            let number = |col: Option<usize>, name: &str| -> Result<Option<f64>, String> {
+                match field(col) {
+                    Some(val) => val
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|v| v.is_finite())
+                        .map(Some)
+                        .ok_or_else(|| err(format!("invalid {} \"{}\"", name, val))),
+                    None => Ok(None),
+                }
+            };
+
+            let date = field(Some(date_col)).ok_or_else(|| err("missing date".into()))?;
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| err(format!("invalid date \"{}\", expected YYYY-MM-DD", date)))?;
+
+            let kind = field(Some(kind_col)).unwrap_or_default();
+            let kind: Kind = kind.parse().map_err(|_| err(format!("unknown transaction type \"{}\"", kind)))?;
+
+            let symbol = field(Some(symbol_col)).ok_or_else(|| err("missing symbol".into()))?.to_uppercase();
+            let quantity = number(Some(quantity_col), "quantity")?;
+            let price = number(Some(price_col), "price")?;
+            let fee = number(fee_col, "fee")?.unwrap_or(0.0);
+            let currency = field(currency_col).unwrap_or("USD").to_uppercase();
+
+            let missing = match kind {
+                Kind::Buy | Kind::Sell if quantity.is_none() => Some("quantity"),
+                Kind::Buy | Kind::Sell | Kind::Dividend if price.is_none() => Some("price"),
+                Kind::Split if quantity.map(|q| q <= 0.0).unwrap_or(true) => Some("split ratio in quantity"),
+                _ => None,
+            };
+
+            if let Some(name) = missing {
+                return Err(err(format!("{} transaction requires a {}", format!("{:?}", kind).to_lowercase(), name)));
+            }
+
+            // a zero quantity would divide the lot cost by zero, a negative one open a short lot
+            let invalid = match kind {
+                Kind::Buy | Kind::Sell if quantity.map(|q| q <= 0.0).unwrap_or(false) => {
+                    Some(format!("{:?} quantity must be positive", kind).to_lowercase())
+                }
+                _ if price.map(|p| p < 0.0).unwrap_or(false) => Some("price must not be negative".into()),
+                _ if fee < 0.0 => Some("fee must not be negative".into()),
+                _ => None,
+            };
+
+            if let Some(message) = invalid {
+                return Err(err(message));
+            }
+
+            transactions.push(Transaction {
+                date,
+                kind,
+                symbol,
+                quantity,
+                price,
+                fee,
+                currency,
+            });
+        }
+
+        // stable, so same-day rows keep their file order
+        transactions.sort_by_key(|t| t.date);
+
+        Ok(Self { transactions })
+    }
+
+    pub fn transactions(&self) -> &Vec<Transaction> {
+        &self.transactions
+    }
+
+    /// Replays every transaction up to and including `until` (or all of them).
+    pub fn derive_until(&self, method: LotMethod, until: Option<NaiveDate>) -> Result<Book, String> {
+        let mut book = Book::new(method);
+
+        for t in self.transactions.iter().filter(|t| until.map(|u| t.date <= u).unwrap_or(true)) {
+            book.apply(t)?;
+        }
+
+        Ok(book)
+    }
+
+    pub fn derive(&self, method: LotMethod) -> Result<Book, String> {
+        self.derive_until(method, None)
+    }
+}
+
+/// Splits a CSV line on commas, honouring double-quoted fields.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+
+    fields.push(current);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "date,type,symbol,quantity,price,fee\n";
+
+    fn ledger(rows: &str) -> Ledger {
+        Ledger::parse(&format!("{}{}", HEADER, rows)).unwrap()
+    }
+
+    fn parse_error(rows: &str) -> String {
+        Ledger::parse(&format!("{}{}", HEADER, rows)).unwrap_err()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    /// Two lots of 10 shares, at 10 and then at 20, and 15 shares sold at 30.
+    const TWO_LOTS: &str = "\
+        2021-01-04,buy,AAPL,10,10,\n\
+        2021-02-01,buy,AAPL,10,20,\n\
+        2021-03-01,sell,AAPL,15,30,\n";
+
+    #[test]
+    fn fifo_sells_oldest_lots_first() {
+        let book = ledger(TWO_LOTS).derive(LotMethod::Fifo).unwrap();
+        let holding = book.holding("AAPL").unwrap();
+
+        assert!(close(book.realized[0].cost, 10.0 * 10.0 + 5.0 * 20.0));
+        assert!(close(book.realized_gain("AAPL"), 450.0 - 200.0));
+        assert_eq!(holding.lots.len(), 1);
+        assert!(close(holding.lots[0].shares, 5.0) && close(holding.lots[0].cost, 20.0));
+    }
+
+    #[test]
+    fn lifo_sells_newest_lots_first() {
+        let book = ledger(TWO_LOTS).derive(LotMethod::Lifo).unwrap();
+        let holding = book.holding("AAPL").unwrap();
+
+        assert!(close(book.realized[0].cost, 10.0 * 20.0 + 5.0 * 10.0));
+        assert_eq!(holding.lots.len(), 1);
+        assert!(close(holding.lots[0].shares, 5.0) && close(holding.lots[0].cost, 10.0));
+    }
+
+    #[test]
+    fn average_pools_lots() {
+        let book = ledger(TWO_LOTS).derive(LotMethod::Average).unwrap();
+        let holding = book.holding("AAPL").unwrap();
+
+        assert!(close(book.realized[0].cost, 15.0 * 15.0));
+        assert_eq!(holding.lots.len(), 1);
+        assert!(close(holding.shares(), 5.0) && close(holding.cost_basis(), 75.0));
+    }
+
+    #[test]
+    fn partial_sell_spans_lots() {
+        let rows = "\
+            2021-01-04,buy,AAPL,5,10,\n\
+            2021-01-05,buy,AAPL,5,12,\n\
+            2021-01-06,buy,AAPL,5,14,\n\
+            2021-02-01,sell,AAPL,7,20,\n";
+
+        let book = ledger(rows).derive(LotMethod::Fifo).unwrap();
+        let holding = book.holding("AAPL").unwrap();
+
+        assert!(close(book.realized[0].cost, 5.0 * 10.0 + 2.0 * 12.0));
+        assert_eq!(holding.lots.iter().map(|l| l.shares).collect::<Vec<_>>(), vec![3.0, 5.0]);
+        assert!(close(holding.cost_basis(), 3.0 * 12.0 + 5.0 * 14.0));
+    }
+
+    #[test]
+    fn fees_are_part_of_cost_and_proceeds() {
+        let rows = "\
+            2021-01-04,buy,AAPL,10,10,5\n\
+            2021-02-01,sell,AAPL,10,20,5\n";
+
+        let book = ledger(rows).derive(LotMethod::Fifo).unwrap();
+
+        assert!(close(book.realized[0].cost, 105.0));
+        assert!(close(book.realized[0].proceeds, 195.0));
+    }
+
+    #[test]
+    fn split_scales_shares_and_keeps_cost_basis() {
+        let rows = "\
+            2021-01-04,buy,AAPL,10,100,\n\
+            2021-06-01,split,AAPL,4,,\n\
+            2021-07-01,sell,AAPL,20,30,\n";
+
+        let before = ledger(rows).derive_until(LotMethod::Fifo, NaiveDate::from_ymd_opt(2021, 6, 30)).unwrap();
+        let holding = before.holding("AAPL").unwrap();
+
+        assert!(close(holding.shares(), 40.0) && close(holding.lots[0].cost, 25.0));
+        assert!(close(holding.cost_basis(), 1000.0));
+
+        let after = ledger(rows).derive(LotMethod::Fifo).unwrap();
+        assert!(close(after.realized[0].cost, 500.0));
+    }
+
+    #[test]
+    fn oversell_is_an_error() {
+        let rows = "\
+            2021-01-04,buy,AAPL,10,10,\n\
+            2021-02-01,sell,AAPL,11,20,\n";
+
+        for method in [LotMethod::Fifo, LotMethod::Lifo, LotMethod::Average].iter() {
+            let e = ledger(rows).derive(*method).unwrap_err();
+            assert!(e.contains("only 10.0000 held"), "{}", e);
+        }
+    }
+
+    #[test]
+    fn rejects_non_positive_quantities() {
+        assert_eq!(parse_error("2021-01-04,buy,AAPL,0,10,\n"), "line 2: buy quantity must be positive");
+        assert_eq!(parse_error("2021-01-04,buy,AAPL,-5,10,\n"), "line 2: buy quantity must be positive");
+        assert_eq!(parse_error("2021-01-04,sell,AAPL,-5,10,\n"), "line 2: sell quantity must be positive");
+    }
+
+    #[test]
+    fn rejects_negative_prices_and_fees() {
+        let rows = "2021-01-04,buy,AAPL,1,10,\n2021-01-05,buy,AAPL,1,-10,\n";
+        assert_eq!(parse_error(rows), "line 3: price must not be negative");
+
+        assert_eq!(parse_error("2021-01-04,fee,AAPL,,,-1\n"), "line 2: fee must not be negative");
+        assert_eq!(parse_error("2021-01-04,buy,AAPL,NaN,10,\n"), "line 2: invalid quantity \"NaN\"");
+    }
+}
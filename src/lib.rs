@@ -1,8 +1,25 @@
 pub mod ui;
 pub use ui::event;
 
+pub mod action;
+pub mod alert;
+pub mod allocation;
 pub mod app;
+pub mod cli;
 pub mod config;
+pub mod correlation;
+pub mod export;
 pub mod ticker;
 pub mod utils;
+pub mod watchlist;
+pub mod ledger;
 pub mod message;
+pub mod portfolio;
+pub mod provider;
+pub mod quote;
+pub mod range;
+pub mod request;
+pub mod risk;
+pub mod snapshot;
+pub mod stats;
+pub mod store;
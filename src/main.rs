@@ -1,44 +1,50 @@
 use tuinance::{
-    app::{App, GraphType, State},
-    config::Config,
+    action::Dispatcher,
+    alert::Alerts,
+    allocation::{self, Grouping},
+    app::{GraphType, Prompt, PromptKind, State, Viewport},
+    cli::{Cli, Command, Format, Output, USAGE},
+    config::{Config, ConfigError},
+    correlation::Matrix,
+    export,
+    snapshot,
+    stats::Statistics,
+    store::{self, Store},
     event::*,
     message::*,
-    ticker::{Data, Ticker},
+    portfolio::Portfolio,
+    provider::Provider,
+    quote,
+    range::DateRange,
+    request::Requests,
+    risk::{self, Risk},
+    ticker::Ticker,
     utils::*,
-    ui::utils::generate_chunks
+    ui::{self, heatmap::Sizing, theme::Theme, utils::generate_chunks},
+    watchlist::{Column, Watchlist},
 };
 
-use yahoo_finance::{Interval, history, Profile, Streamer, Timestamped};
-use futures::future;
-use std::sync::mpsc::{self, Sender, Receiver};
-
-use ordered_float::OrderedFloat;
+use yahoo_finance::Interval;
 
 use tui::{
-    backend::CrosstermBackend,
+    backend::{CrosstermBackend, TestBackend},
     layout::{
         Constraint,
         Direction,
         Rect,
         Layout,
     },
-    symbols,
     style::{
         Style,
         Modifier,
-        Color
     },
     text::{Span, Spans},
     widgets::{
-        Axis,
         Borders,
         Block,
         List,
         ListItem,
-        Chart,
-        Dataset,
         Sparkline,
-        GraphType as TuiGraphType,
     },
     Terminal,
 };
@@ -53,37 +59,246 @@ use crossterm::{
     }
 };
 
-use std::cmp::Ordering;
-use futures::StreamExt;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::io::Write;
+use std::time::{Duration, Instant};
+use std::borrow::Cow;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use futures::future::Abortable;
+
+/// How long an alert popup stays up when it is not dismissed.
+const POPUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn get_interval_data(provider: Provider, symbol: &str, interval: Interval, generation: u64, tx: UnboundedSender<Message>) {
+    let data = provider.history(symbol, interval).await.map_err(|e| e.to_string());
+    tx.send(Message::DataUpdate((symbol.to_string(), generation, data))).unwrap();
+}
+
+async fn get_year_range(provider: Provider, symbol: &str, tx: UnboundedSender<Message>) {
+    if let Ok(data) = provider.history(symbol, Interval::_1y).await {
+        let low = data.low_data().iter().cloned().fold(f64::INFINITY, f64::min);
+        let high = data.high_data().iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        if low.is_finite() && high.is_finite() {
+            tx.send(Message::YearRange((symbol.to_string(), (low, high)))).unwrap();
+        }
+    }
+}
+
+async fn get_market_cap(provider: Provider, symbol: &str, tx: UnboundedSender<Message>) {
+    if let Ok(Some(cap)) = provider.market_cap(symbol).await {
+        tx.send(Message::MarketCap((symbol.to_string(), cap))).unwrap();
+    }
+}
+
+async fn get_profile(provider: Provider, symbol: &str, tx: UnboundedSender<Message>) {
+    if let Ok(profile) = provider.profile(symbol).await {
+        tx.send(Message::ProfileInit((symbol.to_string(), profile))).unwrap();
+    }
+}
+
+/// Loads `symbol` over `interval`, cancelling its previous request. The ticker
+/// keeps charting its current period until the new history arrives.
+fn request_interval(
+    provider: Provider,
+    tx: &UnboundedSender<Message>,
+    store: &mut Store,
+    requests: &mut Requests,
+    symbol: String,
+    interval: Interval,
+) {
+    let tx = tx.clone();
+    let (generation, registration) = requests.issue(&symbol);
+
+    if let Some(ticker) = store.get_mut(&symbol) {
+        ticker.request(generation, interval, None);
+    }
 
-async fn get_interval_data(symbol: &str, interval: Interval, tx: Sender<Message>) {
-    let hist = history::retrieve_interval(symbol, interval).await.unwrap_or(vec![]);
+    tokio::spawn(Abortable::new(async move {
+        get_interval_data(provider, &symbol, interval, generation, tx).await;
+    }, registration));
+}
 
-    let mut price_data = vec![];
-    let mut date_data = vec![];
-    let mut volume_data = vec![];
+async fn get_range_data(provider: Provider, symbol: &str, range: DateRange, generation: u64, tx: UnboundedSender<Message>) {
+    let data = provider.history_range(symbol, range).await.map_err(|e| e.to_string());
+    tx.send(Message::DataUpdate((symbol.to_string(), generation, data))).unwrap();
+}
 
-    for d in hist.iter() {
-        let date = format!("{}", d.datetime().format("%b %e %Y"));
-        date_data.push(date);
-        
-        price_data.push(OrderedFloat::from(d.close));
-        volume_data.push(d.volume.unwrap());
+/// Loads `symbol` over `range`, cancelling its previous request.
+fn request_range(
+    provider: Provider,
+    tx: &UnboundedSender<Message>,
+    store: &mut Store,
+    requests: &mut Requests,
+    symbol: String,
+    range: DateRange,
+) {
+    let tx = tx.clone();
+    let (generation, registration) = requests.issue(&symbol);
+
+    if let Some(ticker) = store.get_mut(&symbol) {
+        let (interval, _) = ticker.requested_period();
+        ticker.request(generation, interval, Some(range));
     }
 
-    let data = Data::new(price_data, date_data, volume_data);
-    tx.send(Message::DataUpdate((symbol.to_string(), data))).unwrap();
+    tokio::spawn(Abortable::new(async move {
+        get_range_data(provider, &symbol, range, generation, tx).await;
+    }, registration));
 }
 
-async fn get_profile(symbol: &str, tx: Sender<Message>) {
-    let profile = Profile::load(symbol).await.unwrap();
-    tx.send(Message::ProfileInit((symbol.to_string(), profile))).unwrap();
+/// Loads `symbol` over a charted period: the custom `range` if any, or `interval`.
+fn request_period(
+    provider: Provider,
+    tx: &UnboundedSender<Message>,
+    store: &mut Store,
+    requests: &mut Requests,
+    symbol: String,
+    (interval, range): (Interval, Option<DateRange>),
+) {
+    match range {
+        Some(range) => request_range(provider, tx, store, requests, symbol, range),
+        None => request_interval(provider, tx, store, requests, symbol, interval),
+    }
+}
+
+/// Whether two periods load the same history; `Interval` has no `PartialEq`.
+fn same_period(a: (Interval, Option<DateRange>), b: (Interval, Option<DateRange>)) -> bool {
+    match (a.1, b.1) {
+        (None, None) => a.0.to_string() == b.0.to_string(),
+        (a, b) => a == b,
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = match Cli::from_env() {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("tuinance: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let config_path = Config::path(cli.config.as_deref());
+
+    match cli.command {
+        Command::Help => {
+            print!("{}", USAGE);
+            return Ok(());
+        }
+        Command::Version => {
+            println!("tuinance {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Command::ConfigPath => {
+            let path = config_path.ok_or("could not determine a configuration path, use --config")?;
+            println!("{}", path.display());
+            return Ok(());
+        }
+        Command::ConfigInit => {
+            let path = config_path.ok_or("could not determine a configuration path, use --config")?;
+            Config::init(&path)?;
+            println!("Wrote example configuration to {}", path.display());
+            return Ok(());
+        }
+        Command::ConfigCheck => {
+            let path = config_path.ok_or("could not determine a configuration path, use --config")?;
+            std::process::exit(check_config(&path, Config::is_explicit(cli.config.as_deref())));
+        }
+        Command::Quote => {
+            let provider = cli.provider.unwrap_or(Provider::Yahoo);
+            std::process::exit(print_quotes(provider, &cli.tickers, cli.text_format().unwrap_or(Format::Table)).await);
+        }
+        Command::Watch => {
+            let provider = cli.provider.unwrap_or(Provider::Yahoo);
+            watch_quotes(provider, cli.tickers.clone(), cli.text_format().unwrap_or(Format::Table)).await?;
+            return Ok(());
+        }
+        Command::History => {
+            let provider = cli.provider.unwrap_or(Provider::Yahoo);
+            let interval = cli.interval.unwrap_or(Interval::_6mo);
+            let format = cli.format.unwrap_or(Output::Text(Format::Csv));
+
+            std::process::exit(print_history(provider, &cli.tickers[0], interval, format, cli.output.as_deref()).await);
+        }
+        Command::Tui | Command::Snapshot | Command::Risk => (),
+    }
+
+    let (conf, warnings) = match Config::load(cli.config.as_deref()) {
+        Ok(val) => val,
+        Err(e) => {
+            eprintln!("tuinance: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let theme = match conf.theme(cli.theme.as_deref()) {
+        Some(theme) => theme,
+        None => {
+            let name = cli.theme.as_deref().or_else(|| conf.theme_name()).unwrap_or_default();
+            eprintln!("tuinance: unknown theme \"{}\" (available: {})", name, Theme::NAMES.join(", "));
+            std::process::exit(2);
+        }
+    };
+
+    let provider = cli.provider.unwrap_or(Provider::Yahoo);
+
+    if cli.command == Command::Snapshot {
+        let interval = cli.interval.or_else(|| conf.interval()).unwrap_or(Interval::_6mo);
+        std::process::exit(snapshot_chart(provider, &cli, interval, &theme).await);
+    }
+
+    let base = config_path.as_ref().and_then(|p| p.parent());
+
+    let portfolio = match Portfolio::load(conf.portfolio(), base) {
+        Ok(portfolio) => portfolio,
+        Err(e) => {
+            eprintln!("tuinance: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let benchmark = cli.benchmark.clone().or_else(|| conf.benchmark().map(|b| b.to_uppercase()));
+
+    if cli.command == Command::Risk {
+        let interval = cli.interval.or_else(|| conf.interval()).unwrap_or(Interval::_6mo);
+        let format = cli.text_format().unwrap_or(Format::Table);
+
+        let (symbols, portfolio) = match cli.tickers.is_empty() {
+            true => (conf.tickers().iter().map(|s| s.to_string()).collect(), Some(&portfolio)),
+            false => (cli.tickers.clone(), None),
+        };
+
+        let code = print_risk(provider, &symbols, portfolio, benchmark, conf.risk_free_rate(), interval, format).await;
+        std::process::exit(code);
+    }
+
+    let mut alerts = match Alerts::from_config(conf.alerts()) {
+        Ok(alerts) => alerts,
+        Err(e) => {
+            eprintln!("tuinance: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let alert_symbols: Vec<String> = alerts.rules().iter().map(|r| r.symbol().clone()).collect();
+
+    let mut tickers_str: Vec<String> = match cli.tickers.is_empty() {
+        true => conf.tickers().into_iter().map(store::normalize).collect(),
+        false => cli.tickers.clone(),
+    };
+
+    // held, alerted and benchmark symbols need prices even when they are not on the watchlist
+    let extra = portfolio.symbols().into_iter()
+        .chain(alert_symbols.iter().map(|s| s.as_str()))
+        .chain(benchmark.as_deref())
+        .map(store::normalize);
+
+    for symbol in extra {
+        if !tickers_str.contains(&symbol) {
+            tickers_str.push(symbol);
+        }
+    }
+
     enable_raw_mode()?;
 
     let mut stdout = std::io::stdout();
@@ -91,52 +306,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut args = std::env::args();
-    args.next();
+    let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
 
-    let user = std::env::var("USER")?;
+    let tickers: Vec<Ticker> = tickers_str.iter().map(|t| {
+        let mut ticker = Ticker::new(t.to_string());
 
-    let conf = match Config::read(&format!("/home/{}/.config/tuinance.toml", user)) {
-        Ok(val) => val,
-        Err(_) => Config::default()
-    };
+        if let Some(interval) = cli.interval.or_else(|| conf.interval()) {
+            ticker.set_interval(interval);
+        }
 
-    let tickers_str = conf.tickers();
+        ticker
+    }).collect();
 
-    let (tx, rx) = mpsc::channel::<Message>();
-    let tx_clone = tx.clone();
+    let mut store = Store::new(tickers);
+    let mut dispatcher = Dispatcher::new(conf.actions().clone());
+    let mut requests = Requests::default();
 
-    let tickers: Vec<Ticker> = tickers_str.iter().map(|t| {
-        Ticker::new(t.to_string())
-    }).collect();
+    tx.send(Message::Start).unwrap();
 
-    let tickers = Arc::new(Mutex::new(tickers));
-    let tickers_clone = tickers.clone();
+    let mut watchlist = Watchlist::new(conf.columns());
 
-    std::thread::spawn(move || {
-        event_loop(rx, tickers_clone, tx_clone);
-    });
+    // the 52-week range is independent of the charted interval
+    if watchlist.columns().contains(&Column::Range) {
+        for symbol in tickers_str.iter().map(|s| s.to_string()) {
+            let tx = tx.clone();
 
-    tx.send(Message::Start).unwrap();
+            tokio::spawn(async move {
+                get_year_range(provider, &symbol, tx).await;
+            });
+        }
+    }
 
-    terminal.clear()?;
-    let mut size = terminal.size()?;
+    // fetched once, for the watchlist column and the market map
+    for symbol in tickers_str.iter().map(|s| s.to_string()) {
+        let tx = tx.clone();
 
-    let events = Events::new(250);
+        tokio::spawn(async move {
+            get_market_cap(provider, &symbol, tx).await;
+        });
+    }
 
-    let streamer = Streamer::new(tickers_str);
+    terminal.clear()?;
+    let mut size = terminal.size()?;
 
-    let mut graph_type = GraphType::Price;
+    let mut events = Events::new(250);
+
+    let symbols: Vec<String> = tickers_str.iter().map(|s| s.to_string()).collect();
+
+    let mut state = State::Main;
+    let mut grouping = Grouping::Sector;
+    let mut graph_type = cli.graph.clone().unwrap_or(GraphType::Price);
+    let mut prompt: Option<Prompt> = None;
+    let mut popup = None;
+    let mut alert_index: usize = 0;
+    let mut pair: (usize, usize) = (0, 0);
+    let mut heat_index: usize = 0;
+    let mut viewport = Viewport::default();
+    let mut sizing = match portfolio.is_empty() {
+        true => Sizing::MarketCap,
+        false => Sizing::Value,
+    };
+    let mut seen_alerts: usize = 0;
+    let mut snapshot_pending = false;
 
     let tx_clone = tx.clone();
 
     tokio::spawn(async move {
-        streamer.stream().await
-        .for_each(move |quote| {
-            tx_clone.send(Message::PriceUpdate((quote.symbol, quote.price))).unwrap();
-            future::ready(())
-        })
-        .await;
+        provider.stream(symbols, move |quote| {
+            tx_clone.send(Message::PriceUpdate((quote.symbol.clone(), quote.price, quote.volume))).is_ok()
+        }).await;
     });
 
     let mut chunks: (Vec<Rect>, Vec<Rect>) = (vec![], vec![]);
@@ -148,244 +386,495 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Constraint::Percentage(20)
         ]).split(size);
 
-    let p = &OrderedFloat::from(0.0);
-
-    let mut render_list = true;
+    let mut render_list = !cli.fullscreen;
     let mut is_first_render = true;
     let mut current_index: usize = 0;
-    let current_error = String::new();
+    let mut status = String::new();
+    let current_error = match warnings.len() {
+        0 => String::new(),
+        n => format!("[{} config warning(s), run `tuinance config check`]", n),
+    };
+
+    // frames are only drawn when the store revision, the terminal size or the
+    // UI state changed since the last one
+    let mut drawn: Option<u64> = None;
+    let mut redraw = true;
+    let mut len = 0;
+    let mut weights = vec![];
+
+    // the correlation matrix and the store revision it was computed at
+    let mut matrix = Matrix::default();
+    let mut matrix_revision: Option<u64> = None;
 
     loop {
-        let tickers_lock = tickers.lock().await;
-        let mut tickers = tickers_lock.clone();
-        drop(tickers_lock);
+        if alerts.fired() > seen_alerts {
+            seen_alerts = alerts.fired();
 
-        let ticker = tickers.get(current_index).unwrap();
+            // ring the terminal bell
+            let mut stdout = std::io::stdout();
+            stdout.write_all(b"\x07")?;
+            stdout.flush()?;
 
-        let data = match graph_type {
-            GraphType::Price => ticker.price_data(),
-            GraphType::Volume => ticker.volume_data_f64()
-        };
+            popup = alerts.history().last().cloned().map(|n| (n, Instant::now()));
+            redraw = true;
+        }
 
-        let volume_data = ticker.volume_data();
+        if popup.as_ref().map(|(_, shown): &(_, Instant)| shown.elapsed() > POPUP_TIMEOUT).unwrap_or(false) {
+            popup = None;
+            redraw = true;
+        }
 
-        let y = ticker.date_data();
+        if let Ok(s) = terminal.size() {
+            if is_first_render || size != s {
+                chunks = generate_chunks(s, render_list);
+                is_first_render = false;
+                size = s;
+                redraw = true;
+            }
+        }
 
-        let len = data.len();
-        let floats: Vec<(f64, f64)> = data.iter().enumerate()
-            .map(|(idx, &elem)| (idx as f64 + 1.0, f64::from(elem)))
-            .collect();
+        if redraw || drawn != Some(store.revision()) {
+            let tickers = store.tickers();
+            let ticker = &tickers[current_index];
 
-        let min = f64::from(data.iter().min().unwrap_or(&p).clone());
-        let max = f64::from(data.iter().max().unwrap_or(&p).clone());
+            let history = match graph_type {
+                GraphType::Portfolio => portfolio.value_history(tickers),
+                _ => Default::default(),
+            };
 
-        let f_date = y.first().unwrap_or(&String::new()).to_string();
-        let m_date = y.get(y.len() / 2).unwrap_or(&String::new()).to_string();
-        let l_date = y.last().unwrap_or(&String::new()).to_string();
+            // the chart, volume and info pane only cover the zoomed-in bars
+            len = match graph_type {
+                GraphType::Portfolio => history.dates.len(),
+                _ => ticker.data().len(),
+            };
 
-        let datasets = vec![
-            Dataset::default()
-                .name(ticker.identifier())
-                .marker(symbols::Marker::Braille)
-                .graph_type(TuiGraphType::Line)
-                .style(Style::default().fg(Color::Green))
-                .data(&floats),
-        ];
+            let range = viewport.range(len);
 
-        let t: Vec<ListItem> = tickers.iter().enumerate().map(|(idx, elem)| {
-            let style = match idx.cmp(&current_index) {
-                Ordering::Equal => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-                _ => Style::default()
+            let (visible, history) = match graph_type {
+                GraphType::Portfolio => (Cow::Borrowed(ticker), history.slice(range.clone())),
+                _ => match viewport.is_zoomed() {
+                    true => (Cow::Owned(ticker.slice(range.clone())), history),
+                    false => (Cow::Borrowed(ticker), history),
+                },
             };
 
-            let name = match elem.info().name().is_empty() {
-                true => elem.identifier(),
-                false => elem.info().name()
+            let volume_data = visible.volume_data();
+
+            let zoom = match viewport.is_zoomed() {
+                true => format!("[bars {}-{} of {}]", range.start + 1, range.end, len),
+                false => String::new(),
             };
 
-            let span = Span::styled(name, style);
+            // the portfolio chart waits on every holding
+            let charted: Vec<&Ticker> = match graph_type {
+                GraphType::Portfolio => portfolio.symbols().iter().filter_map(|s| store.get(s)).collect(),
+                _ => vec![ticker],
+            };
 
-            ListItem::new(span)
-        }).collect();
+            let failed = charted.iter().find_map(|t| t.error().map(|e| (t.identifier(), e)));
 
-        let list = List::new(t)
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Rgb(53, 59, 69)))
-            );
+            let loading = match (charted.iter().any(|t| t.is_loading()), failed) {
+                (true, _) => "[loading]".to_string(),
+                (false, Some((symbol, e))) => format!("[could not load {}: {}]", symbol, e),
+                (false, None) => String::new(),
+            };
 
-        let title = match graph_type {
-            GraphType::Price => "Price",
-            GraphType::Volume => "Volume",
-        };
+            let title_status = format!("{}{}{}{}", current_error, loading, zoom, status);
 
-        let chart = Chart::new(datasets)
-            .block(Block::default()
-                   .title(Span::styled(
-                        format!("TUInance - {} ({}) {}", ticker.identifier(), ticker.interval().to_string(), current_error),
-                        Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD))
-                    )
-                   .borders(Borders::ALL)
-                   .border_style(Style::default().fg(Color::Rgb(53, 59, 69)))
-            )
-            .style(Style::default().fg(Color::White))
-            .x_axis(Axis::default()
-                .title(Span::styled(
-                    "Date",
-                    Style::default().fg(Color::Yellow)
-                ))
-                .style(Style::default().fg(Color::Rgb(53, 59, 69)))
-                .bounds([0.0, len as f64])
-                .labels([f_date, m_date, l_date]
-                    .iter()
-                    .cloned()
-                    .map(|x| Span::styled(x, Style::default().fg(Color::Yellow)))
-                    .collect()
-                )
-            )
-
-            .y_axis(Axis::default()
-                .title(Span::styled(title, Style::default().fg(Color::Yellow)))
-                .style(Style::default().fg(Color::Rgb(53, 59, 69)))
-                .bounds([min, max])
-                .labels([format!("{:.3}", min), format!("{:.3}", min + (max - min) / 2.0), format!("{:.3}", max)]
-                    .iter()
-                    .cloned()
-                    .map(|x| Span::styled(x, Style::default().fg(Color::Yellow)))
-                    .collect()
+            let mut info_spans = vec![
+                Spans::from(vec![
+                    Span::styled("Current Price: ", Style::default().fg(theme.label)),
+                    Span::styled(format!("${}", ticker.realtime_price().to_string()), Style::default().fg(theme.title))
+                ])
+            ];
+
+            if let Some(book) = portfolio.book() {
+                info_spans.extend(ui::portfolio::lot_spans(book, ticker.identifier(), &theme));
+            }
+
+            match graph_type {
+                GraphType::Portfolio => info_spans.extend(ui::portfolio::return_spans(&history, &theme)),
+                _ => {
+                    if let Some(stats) = Statistics::compute(visible.data()) {
+                        info_spans.extend(ui::stats::spans(&stats, &theme));
+                    }
+                }
+            }
+
+            let returns = match graph_type {
+                GraphType::Portfolio => history.returns(),
+                _ => risk::returns(visible.data()),
+            };
+
+            if !returns.is_empty() {
+                let benchmark_returns = benchmark
+                    .as_ref()
+                    .and_then(|b| store.get(b))
+                    .map(|t| risk::returns(t.data()));
+
+                let risk = Risk::compute(&returns, benchmark_returns.as_deref(), conf.risk_free_rate());
+                info_spans.extend(ui::stats::risk_spans(&risk, benchmark.as_deref(), &theme));
+            }
+
+            let info_list: Vec<ListItem> = info_spans.into_iter().map(ListItem::new).collect();
+
+            let info = List::new(info_list)
+                .block(Block::default()
+                    .borders(Borders::ALL)
+                    .title(Span::styled(ticker.identifier(), Style::default().fg(theme.title).add_modifier(Modifier::BOLD)))
+                    .border_style(Style::default().fg(theme.border))
+                );
+
+            let barchart = Sparkline::default()
+                .block(Block::default()
+                       .title(Span::styled("Volume", Style::default().fg(theme.title).add_modifier(Modifier::BOLD)))
+                       .borders(Borders::ALL)
+                       .border_style(
+                           Style::default()
+                           .fg(theme.border)
+                        )
                 )
-            );
+                .data(&volume_data)
+                .style(Style::default().fg(theme.volume));
+                /*.bar_style(Style::default().fg(Color::Yellow))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));*/
+
+            let summary = match state {
+                State::Portfolio | State::Allocation | State::Heatmap => portfolio.summarize(tickers),
+                _ => Default::default(),
+            };
 
+            let allocation = match state {
+                State::Allocation => allocation::allocate(
+                    &summary,
+                    tickers,
+                    grouping,
+                    portfolio.tags(),
+                    portfolio.targets(grouping.key()),
+                ),
+                _ => Default::default(),
+            };
 
-        if let Ok(s) = terminal.size() {
-            if is_first_render || size != s {
-                chunks = generate_chunks(s, render_list);
-                is_first_render = false;
-                size = s;
+            // recomputed only once the tickers changed, not on every redraw
+            if state == State::Correlation && matrix_revision != Some(store.revision()) {
+                matrix = Matrix::compute(tickers);
+                matrix_revision = Some(store.revision());
             }
-        }
 
-        let info_spans = vec![
-            Spans::from(vec![
-                Span::styled("Current Price: ", Style::default().fg(Color::Blue)),
-                Span::styled(format!("${}", ticker.realtime_price().to_string()), Style::default().fg(Color::Yellow))
-            ])
-        ];
-
-        let info_list: Vec<ListItem> = info_spans.iter().map(|elem| ListItem::new(elem.clone())).collect();
-
-        let info = List::new(info_list)
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .title(Span::styled(ticker.identifier(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)))
-                .border_style(Style::default().fg(Color::Rgb(53, 59, 69)))
-            );
-
-        let barchart = Sparkline::default()
-            .block(Block::default()
-                   .title(Span::styled("Volume", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)))
-                   .borders(Borders::ALL)
-                   .border_style(
-                       Style::default()
-                       .fg(Color::Rgb(53, 59, 69))
-                    )
-            )
-            .data(&volume_data)
-            .style(Style::default().fg(Color::Magenta));
-            /*.bar_style(Style::default().fg(Color::Yellow))
-            .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));*/
-
-        terminal.draw(|f| {
-            match render_list {
-                true => {
-                    f.render_widget(chart, chunks.1[0]);
-                    f.render_widget(list, chunks.0[0]);
-                    f.render_widget(info, chunks.0[1]);
-                    f.render_widget(barchart, chunks.1[1]);
+            weights = match state {
+                State::Heatmap => ui::heatmap::weights(tickers, &summary, sizing),
+                _ => vec![],
+            };
+
+            // tiles too small to draw, e.g. of the ticker selected when opening the map, cannot be selected
+            if state == State::Heatmap {
+                let area = Block::default().borders(Borders::ALL).inner(size);
+                heat_index = ui::heatmap::visible(&ui::heatmap::tiles(area, &weights), heat_index);
+            }
+
+            let frame = terminal.draw(|f| {
+                match state {
+                    State::Portfolio => ui::portfolio::render(f, f.size(), &summary, &theme),
+                    State::Allocation => ui::allocation::render(f, f.size(), &allocation, grouping, &theme),
+                    State::Alerts => ui::alerts::render(f, f.size(), &alerts, alert_index, &theme),
+                    State::Correlation => ui::correlation::render(f, f.size(), &matrix, pair, &ticker.period(), &theme),
+                    State::Heatmap => ui::heatmap::render(f, f.size(), tickers, &weights, heat_index, sizing, &theme),
+                    _ => match render_list {
+                        true => {
+                            ui::chart::render(f, chunks.1[0], &visible, &graph_type, &history, &title_status, &theme);
+                            ui::watchlist::render(f, chunks.0[0], tickers, &watchlist, current_index, &alerts, &theme);
+                            f.render_widget(info, chunks.0[1]);
+                            f.render_widget(barchart, chunks.1[1]);
+                        }
+                        false => {
+                            ui::chart::render(f, chunks.1[0], &visible, &graph_type, &history, &title_status, &theme);
+                        }
+                    },
+                }
+
+                if let Some((notification, _)) = &popup {
+                    ui::alerts::popup(f, f.size(), notification, &theme);
                 }
-                false => {
-                    f.render_widget(chart, chunks.1[0]);
+
+                if let Some(prompt) = &prompt {
+                    ui::prompt::render(f, f.size(), prompt, &theme);
                 }
+            })?;
+
+            if snapshot_pending {
+                snapshot_pending = false;
+
+                let name = format!("{}-{}", ticker.identifier(), chrono::Local::now().format("%Y%m%d-%H%M%S"));
+                let svg = format!("{}.svg", name);
+                let txt = format!("{}.txt", name);
+
+                let written = snapshot::write(frame.buffer, svg.as_ref())
+                    .and_then(|_| snapshot::write(frame.buffer, txt.as_ref()));
+
+                status = match written {
+                    Ok(()) => format!("[saved snapshot to {} and {}]", svg, txt),
+                    Err(e) => format!("[snapshot failed: {}]", e),
+                };
             }
-        })?;
-
-        if let Ok(ev) = events.next() {
-            match ev {
-                Event::Input(i) => {
-                    match i {
-                        Key::Char(c) => {
-                            match c {
-                                'q' => break,
-                                'z' => {
-                                    render_list = !render_list;
-                                    chunks = generate_chunks(size, render_list);
-                                }
-                                'j' => {
-                                    if current_index + 1 < tickers.len() {
-                                        current_index += 1;
-                                    }
-                                }
-                                'k' => {
-                                    if current_index >= 1 {
-                                        current_index -= 1;
+
+            drawn = Some(store.revision());
+        }
+
+        // responses and quotes are applied as they arrive and drawn with the
+        // next tick or key press
+        let ev = loop {
+            tokio::select! {
+                ev = events.next() => break ev,
+                Some(msg) = rx.recv() => handle_message(provider, msg, &mut store, &mut alerts, &mut dispatcher, &mut requests, &tx),
+            }
+        };
+
+        let ev = match ev {
+            Some(ev) => ev,
+            None => break,
+        };
+
+        // ticks only wake the loop to check for changes
+        redraw = !matches!(ev, Event::Tick);
+
+        let tickers = store.tickers();
+        let ticker = &tickers[current_index];
+
+        match ev {
+            Event::Input(i) if prompt.is_some() => {
+                let current = prompt.as_mut().unwrap();
+
+                match i {
+                    Key::Char(c) => current.push(c),
+                    Key::Backspace => current.pop(),
+                    Key::Esc => prompt = None,
+                    Key::Enter => match current.kind() {
+                        PromptKind::Alert => {
+                            let added = alerts.add_from_str(current.input(), ticker.identifier()).and_then(|id| {
+                                let rule = alerts.rules().iter().find(|r| r.id() == id).unwrap();
+
+                                match store.contains(rule.symbol()) {
+                                    true => Ok(()),
+                                    false => {
+                                        let message = format!("{} is not on the watchlist", rule.symbol());
+                                        alerts.remove(id);
+                                        Err(message)
                                     }
                                 }
-                                'l' => {
-                                    let tx = tx.clone();
-                                    let ticker = tickers.get_mut(current_index).unwrap();
+                            });
 
-                                    let next = next_interval(*ticker.interval());
+                            match added {
+                                Ok(()) => prompt = None,
+                                Err(e) => current.set_error(e),
+                            }
+                        }
+                        PromptKind::Range => match current.input().parse::<DateRange>() {
+                            Ok(range) => {
+                                let mut symbols = vec![ticker.identifier().clone()];
 
-                                    let symbol = ticker.identifier().clone();
-                                    let interval = next.clone();
+                                if let GraphType::Portfolio = graph_type {
+                                    symbols.extend(portfolio.symbols().iter().map(|s| s.to_string()));
+                                }
 
-                                    tx.send(Message::SetInterval((symbol.clone(), next))).unwrap();
-                                    //ticker.set_interval(next).await;
+                                if let Some(benchmark) = benchmark.as_ref().filter(|b| !symbols.contains(b)) {
+                                    symbols.push(benchmark.clone());
+                                }
 
-                                    tokio::spawn(async move {
-                                        get_interval_data(&symbol, interval, tx).await;
-                                    });
+                                for symbol in symbols {
+                                    request_range(provider, &tx, &mut store, &mut requests, symbol, range);
                                 }
-                                'h' => {
-                                    let tx = tx.clone();
-                                    let ticker = tickers.get_mut(current_index).unwrap();
 
-                                    let prev = previous_interval(*ticker.interval());
+                                viewport.reset();
+                                prompt = None;
+                            }
+                            Err(e) => current.set_error(e),
+                        },
+                    },
+                    _ => (),
+                }
+            }
+            Event::Input(Key::Esc) | Event::Input(Key::Enter) if popup.is_some() => popup = None,
+            Event::Input(i) => {
+                match i {
+                    Key::Enter if state == State::Heatmap => {
+                        current_index = heat_index;
+                        state = State::Main;
+                    }
+                    Key::Char(c) => {
+                        match c {
+                            'q' => break,
+                            'n' => prompt = Some(Prompt::new(PromptKind::Alert)),
+                            'r' => prompt = Some(Prompt::new(PromptKind::Range)),
+                            '!' => {
+                                state = match state {
+                                    State::Alerts => State::Main,
+                                    _ => State::Alerts,
+                                };
+                            }
+                            'j' if state == State::Alerts => {
+                                alert_index = (alert_index + 1).min(alerts.rules().len().saturating_sub(1));
+                            }
+                            'k' if state == State::Alerts => alert_index = alert_index.saturating_sub(1),
+                            'd' if state == State::Alerts => {
+                                if let Some(id) = alerts.rules().get(alert_index).map(|r| r.id()) {
+                                    alerts.remove(id);
+                                    alert_index = alert_index.min(alerts.rules().len().saturating_sub(1));
+                                }
+                            }
+                            'c' => {
+                                state = match state {
+                                    State::Correlation => State::Main,
+                                    _ => State::Correlation,
+                                };
+
+                                // the matrix compares every ticker over the selected one's period
+                                if state == State::Correlation {
+                                    let period = ticker.requested_period();
+                                    let others: Vec<String> = tickers.iter()
+                                        .filter(|t| !same_period(t.requested_period(), period))
+                                        .map(|t| t.identifier().clone())
+                                        .collect();
+
+                                    for other in others {
+                                        request_period(provider, &tx, &mut store, &mut requests, other, period);
+                                    }
+                                }
+                            }
+                            'j' | 'k' | 'h' | 'l' if state == State::Correlation => {
+                                let last = tickers.len().saturating_sub(1);
+
+                                pair = match c {
+                                    'j' => ((pair.0 + 1).min(last), pair.1),
+                                    'k' => (pair.0.saturating_sub(1), pair.1),
+                                    'l' => (pair.0, (pair.1 + 1).min(last)),
+                                    _ => (pair.0, pair.1.saturating_sub(1)),
+                                };
+                            }
+                            'm' => {
+                                state = match state {
+                                    State::Heatmap => State::Main,
+                                    _ => State::Heatmap,
+                                };
 
-                                    let symbol = ticker.identifier().clone();
-                                    let interval = prev.clone();
+                                heat_index = current_index;
+                            }
+                            'g' if state == State::Heatmap => sizing = match sizing.next() {
+                                // there is nothing to size by without a portfolio
+                                Sizing::Value if portfolio.is_empty() => Sizing::Value.next(),
+                                next => next,
+                            },
+                            'j' | 'k' | 'h' | 'l' if state == State::Heatmap => {
+                                let direction = match c {
+                                    'j' => (0, 1),
+                                    'k' => (0, -1),
+                                    'l' => (1, 0),
+                                    _ => (-1, 0),
+                                };
+
+                                let area = Block::default().borders(Borders::ALL).inner(size);
+                                let tiles = ui::heatmap::tiles(area, &weights);
+
+                                heat_index = ui::heatmap::neighbour(&tiles, heat_index, direction);
+                            }
+                            'p' => {
+                                state = match state {
+                                    State::Portfolio => State::Main,
+                                    _ => State::Portfolio,
+                                };
+                            }
+                            'a' => {
+                                state = match state {
+                                    State::Allocation => State::Main,
+                                    _ => State::Allocation,
+                                };
+                            }
+                            'g' if state == State::Allocation => grouping = grouping.next(),
+                            's' => snapshot_pending = true,
+                            'e' => {
+                                let path = format!(
+                                    "{}-{}-{}.{}",
+                                    ticker.identifier(),
+                                    ticker.period().replace(' ', "-"),
+                                    chrono::Local::now().format("%Y%m%d-%H%M%S"),
+                                    export::extension(Format::Csv),
+                                );
+
+                                status = match export::write(ticker.data(), Output::Text(Format::Csv), Some(path.as_ref())) {
+                                    Ok(()) => format!("[exported {} bars to {}]", ticker.data().len(), path),
+                                    Err(e) => format!("[export failed: {}]", e),
+                                };
+                            }
+                            'z' => {
+                                render_list = !render_list;
+                                chunks = generate_chunks(size, render_list);
+                            }
+                            'j' => current_index = watchlist.step(tickers, current_index, 1),
+                            'k' => current_index = watchlist.step(tickers, current_index, -1),
+                            'o' => watchlist.cycle_sort(),
+                            'O' => watchlist.reverse(),
+                            '+' | '=' => viewport.zoom_in(len),
+                            '-' => viewport.zoom_out(len),
+                            '[' => viewport.pan(len, false),
+                            ']' => viewport.pan(len, true),
+                            '0' => viewport.reset(),
+                            'l' | 'h' => {
+                                viewport.reset();
+
+                                let interval = match c {
+                                    // step from the interval still loading, if any
+                                    'l' => next_interval(ticker.requested_period().0),
+                                    _ => previous_interval(ticker.requested_period().0),
+                                };
+
+                                let mut symbols = vec![ticker.identifier().clone()];
+
+                                // the portfolio chart needs every holding on the same interval
+                                if let GraphType::Portfolio = graph_type {
+                                    symbols.extend(portfolio.symbols().iter().map(|s| s.to_string()));
+                                }
 
-                                    tx.send(Message::SetInterval((symbol.clone(), prev))).unwrap();
+                                // and the risk metrics the benchmark
+                                if let Some(benchmark) = benchmark.as_ref().filter(|b| !symbols.contains(b)) {
+                                    symbols.push(benchmark.clone());
+                                }
 
-                                    tokio::spawn(async move {
-                                        get_interval_data(&symbol, interval, tx).await;
-                                    });
+                                for symbol in symbols {
+                                    request_interval(provider, &tx, &mut store, &mut requests, symbol, interval);
                                 }
+                            }
+
+                            'v' => {
+                                viewport.reset();
 
-                                'v' => {
-                                    graph_type = match graph_type {
-                                        GraphType::Price => GraphType::Volume,
-                                        GraphType::Volume => GraphType::Price
-                                    };
-                                    let tx = tx.clone();
+                                let symbol = ticker.identifier().clone();
+                                let period = ticker.requested_period();
 
-                                    let ident = ticker.identifier().clone();
-                                    let interval = ticker.interval().clone();
+                                graph_type = match graph_type {
+                                    GraphType::Price => GraphType::Volume,
+                                    GraphType::Volume if !portfolio.is_empty() => GraphType::Portfolio,
+                                    _ => GraphType::Price,
+                                };
 
-                                    tokio::spawn(async move {
-                                        get_interval_data(&ident, interval, tx).await;
-                                    });
+                                match graph_type {
+                                    GraphType::Portfolio => {
+                                        for holding in portfolio.symbols() {
+                                            request_period(provider, &tx, &mut store, &mut requests, holding.to_string(), period);
+                                        }
+                                    }
+                                    _ => request_period(provider, &tx, &mut store, &mut requests, symbol.clone(), period),
+                                }
+
+                                if let Some(benchmark) = benchmark.as_ref().filter(|b| **b != symbol) {
+                                    request_period(provider, &tx, &mut store, &mut requests, benchmark.clone(), period);
                                 }
-                                _ => ()
                             }
+                            _ => ()
                         }
-                        _ => ()
                     }
+                    _ => ()
                 }
-                Event::Tick => (),
             }
+            Event::Tick => (),
         }
     }
     exit()?;
@@ -393,73 +882,326 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-#[tokio::main]
-async fn event_loop(rx: Receiver<Message>, tickers: Arc<Mutex<Vec<Ticker>>>, tx: Sender<Message>) {
-    loop {
-        let msg = match rx.recv() {
-            Ok(msg) => msg,
-            Err(_) => break,
-        };
+/// Applies a provider response, quote or action result to the UI state.
+/// Messages for symbols that are not in the store are ignored (see `Store::apply`).
+fn handle_message(
+    provider: Provider,
+    msg: Message,
+    store: &mut Store,
+    alerts: &mut Alerts,
+    dispatcher: &mut Dispatcher,
+    requests: &mut Requests,
+    tx: &UnboundedSender<Message>,
+) {
+    use Message::*;
+
+    match msg {
+        PriceUpdate(quote) => {
+            let volume = quote.2;
+
+            if let Some(ticker) = store.apply(PriceUpdate(quote)) {
+                for notification in alerts.evaluate(ticker, volume) {
+                    let rule = alerts.rule(notification.rule_id);
+
+                    dispatcher.dispatch(
+                        &notification,
+                        rule.and_then(|r| r.command()),
+                        rule.and_then(|r| r.webhook()),
+                        tx,
+                    );
+                }
+            }
+        }
 
-        let mut tickers = tickers.lock().await;
+        ActionFailed(error) => {
+            alerts.record_error(error);
+            store.touch();
+        }
+        Start => {
+            let tickers: Vec<(String, Interval)> = store.tickers().iter()
+                .map(|t| (t.identifier().clone(), *t.interval()))
+                .collect();
 
-        use Message::*;
+            for (identifier, interval) in tickers {
+                let tx_other = tx.clone();
 
-        match msg {
-            SetInterval((symbol, interval)) => {
-                let ticker = tickers
-                    .iter_mut()
-                    .find(|t| t.identifier() == &symbol)
-                    .unwrap();
+                request_interval(provider, tx, store, requests, identifier.clone(), interval);
 
-                ticker.set_interval(interval);
+                tokio::spawn(async move {
+                    get_profile(provider, &identifier, tx_other).await;
+                });
             }
+        }
+        msg => {
+            store.apply(msg);
+        }
+    }
+}
 
-            ProfileInit((symbol, p)) => {
-                let ticker = tickers
-                    .iter_mut()
-                    .find(|t| t.identifier() == &symbol)
-                    .unwrap();
-
-                ticker.init_info(p);
+/// Prints the quotes of `symbols` and returns the process exit code, 1 when a
+/// symbol could not be found.
+async fn print_quotes(provider: Provider, symbols: &[String], format: Format) -> i32 {
+    let mut quotes = vec![];
+    let mut code = 0;
+
+    for result in quote::fetch(provider, symbols).await {
+        match result {
+            Ok(q) => quotes.push(q),
+            Err(e) => {
+                eprintln!("tuinance: {}", e);
+                code = 1;
             }
+        }
+    }
+
+    print!("{}", quote::format(&quotes, format));
+    code
+}
+
+/// Writes streamed quotes to stdout, one per line, until the stream ends or
+/// stdout is closed.
+async fn watch_quotes(provider: Provider, symbols: Vec<String>, format: Format) -> std::io::Result<()> {
+    let mut result = Ok(());
 
-            DataUpdate((symbol, data)) => {
-                let ticker = tickers
-                    .iter_mut()
-                    .find(|t| t.identifier() == &symbol)
-                    .unwrap();
+    if let Some(header) = quote::tick_header(format) {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(header.as_bytes())?;
+        stdout.flush()?;
+    }
+
+    provider.stream(symbols, |q| {
+        let line = quote::format_tick(&quote::Tick::from(q), format);
 
+        let mut stdout = std::io::stdout();
+        result = stdout.write_all(line.as_bytes()).and_then(|_| stdout.flush());
+        result.is_ok()
+    }).await;
+
+    // a closed pipe (e.g. `| head`) is the reader saying it is done
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        other => other,
+    }
+}
+
+/// Writes the bars of `symbol` to `output` (or stdout) and returns the process
+/// exit code.
+async fn print_history(provider: Provider, symbol: &str, interval: Interval, format: Output, output: Option<&str>) -> i32 {
+    let data = match provider.history(symbol, interval).await {
+        Ok(data) if !data.is_empty() => data,
+        Ok(_) => {
+            eprintln!("tuinance: unknown symbol: {}", symbol);
+            return 1;
+        }
+        Err(e) => {
+            eprintln!("tuinance: could not fetch {}: {}", symbol, e);
+            return 1;
+        }
+    };
+
+    match export::write(&data, format, output.map(std::path::Path::new)) {
+        Ok(()) => 0,
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => 0,
+        Err(e) => {
+            eprintln!("tuinance: could not write {}: {}", output.unwrap_or("history"), e);
+            1
+        }
+    }
+}
+
+/// Renders the chart of the first ticker of `cli` off-screen and writes it to
+/// the `--output` file, or as ANSI text to stdout. Returns the process exit code.
+async fn snapshot_chart(provider: Provider, cli: &Cli, interval: Interval, theme: &Theme) -> i32 {
+    let symbol = &cli.tickers[0];
+    let graph_type = cli.graph.clone().unwrap_or(GraphType::Price);
+
+    if let GraphType::Portfolio = graph_type {
+        eprintln!("tuinance: the portfolio chart can only be saved from the TUI (press s)");
+        return 2;
+    }
+
+    let output = cli.output.as_deref().filter(|o| *o != "-").map(std::path::Path::new);
+
+    match output.map(|p| (p, snapshot::SnapshotFormat::from_path(p))) {
+        Some((path, None)) => {
+            eprintln!("tuinance: unsupported snapshot format {} (expected .svg, .ansi, .txt or .png)", path.display());
+            return 2;
+        }
+        Some((_, Some(snapshot::SnapshotFormat::Png))) if !cfg!(feature = "png") => {
+            eprintln!("tuinance: png snapshots require tuinance built with `--features png`");
+            return 2;
+        }
+        _ => (),
+    }
+
+    let mut ticker = Ticker::new(symbol.clone());
+    ticker.set_interval(interval);
+
+    let (data, profile) = futures::future::join(
+        provider.history(symbol, interval),
+        provider.profile(symbol),
+    ).await;
+
+    match data {
+        Ok(data) if !data.is_empty() => ticker.set_data(data),
+        Ok(_) => {
+            eprintln!("tuinance: unknown symbol: {}", symbol);
+            return 1;
+        }
+        Err(e) => {
+            eprintln!("tuinance: could not fetch {}: {}", symbol, e);
+            return 1;
+        }
+    }
+
+    if let Ok(profile) = profile {
+        ticker.init_info(profile);
+    }
+
+    let (cols, rows) = cli.size.unwrap_or((120, 40));
+
+    let mut terminal = match Terminal::new(TestBackend::new(cols, rows)) {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            eprintln!("tuinance: {}", e);
+            return 1;
+        }
+    };
+
+    let history = Default::default();
+
+    let frame = terminal.draw(|f| {
+        ui::chart::render(f, f.size(), &ticker, &graph_type, &history, "", theme);
+    });
+
+    let result = frame.and_then(|frame| match output {
+        Some(path) => snapshot::write(frame.buffer, path),
+        None => {
+            let mut stdout = std::io::stdout();
+            stdout.write_all(snapshot::ansi(frame.buffer).as_bytes())?;
+            stdout.flush()
+        }
+    });
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("tuinance: could not write snapshot: {}", e);
+            1
+        }
+    }
+}
+
+/// Prints the risk metrics of `symbols`, followed by those of the `portfolio`
+/// when given, and returns the process exit code.
+async fn print_risk(
+    provider: Provider,
+    symbols: &[String],
+    portfolio: Option<&Portfolio>,
+    benchmark: Option<String>,
+    risk_free_rate: f64,
+    interval: Interval,
+    format: Format,
+) -> i32 {
+    let portfolio = portfolio.filter(|p| !p.is_empty());
+
+    let mut needed: Vec<String> = vec![];
+
+    let held = portfolio.into_iter().flat_map(|p| p.symbols()).map(|s| s.to_string());
+
+    for symbol in symbols.iter().cloned().chain(held).chain(benchmark.clone()) {
+        if !needed.contains(&symbol) {
+            needed.push(symbol);
+        }
+    }
+
+    let results = futures::future::join_all(needed.iter().map(|s| provider.history(s, interval))).await;
+
+    let mut tickers = vec![];
+    let mut code = 0;
+
+    for (symbol, result) in needed.iter().zip(results) {
+        match result {
+            Ok(data) if !data.is_empty() => {
+                let mut ticker = Ticker::new(symbol.clone());
+                ticker.set_interval(interval);
                 ticker.set_data(data);
+                tickers.push(ticker);
+            }
+            Ok(_) => {
+                eprintln!("tuinance: unknown symbol: {}", symbol);
+                code = 1;
             }
+            Err(e) => {
+                eprintln!("tuinance: could not fetch {}: {}", symbol, e);
+                code = 1;
+            }
+        }
+    }
 
-            PriceUpdate((symbol, price)) => {
-                let ticker = tickers
-                    .iter_mut()
-                    .find(|t| t.identifier() == &symbol)
-                    .unwrap();
+    let benchmark_returns = benchmark
+        .as_ref()
+        .and_then(|b| tickers.iter().find(|t| t.identifier() == b))
+        .map(|t| risk::returns(t.data()));
 
-                ticker.set_realtime_price(price);
+    let report = |symbol: &str, returns: &[(chrono::NaiveDate, f64)]| risk::Report {
+        symbol: symbol.to_string(),
+        benchmark: benchmark.clone(),
+        risk: Risk::compute(returns, benchmark_returns.as_deref(), risk_free_rate),
+    };
+
+    let mut reports: Vec<risk::Report> = symbols
+        .iter()
+        .filter_map(|s| tickers.iter().find(|t| t.identifier() == s))
+        .map(|t| report(t.identifier(), &risk::returns(t.data())))
+        .collect();
+
+    if let Some(portfolio) = portfolio {
+        reports.push(report("PORTFOLIO", &portfolio.value_history(&tickers).returns()));
+    }
+
+    print!("{}", risk::format(&reports, format));
+    code
+}
+
+/// Reports configuration problems on stdout and returns the process exit code.
+fn check_config(path: &std::path::Path, explicit: bool) -> i32 {
+    match Config::read(path) {
+        Ok((conf, warnings)) => {
+            for warning in warnings.iter() {
+                println!("warning: {}", warning);
             }
-            Start => {
-                for t in 0..tickers.len() {
-                    let t = &tickers[t];
-
-                    let identifier = t.identifier().clone();
-                    let identifier_clone = identifier.clone();
-                    let interval = t.interval().clone();
-                    let tx_clone = tx.clone();
-                    let tx_other = tx.clone();
-
-                    tokio::spawn(async move {
-                        get_interval_data(&identifier, interval, tx_clone).await;
-                    });
-
-                    tokio::spawn(async move {
-                        get_profile(&identifier_clone, tx_other).await;
-                    });
-                }
+
+            // the portfolio file and ledger are only read when loading the portfolio
+            if let Err(e) = Portfolio::load(conf.portfolio(), path.parent()) {
+                println!("error: {}", e);
+                println!("{}: invalid", path.display());
+                return 1;
             }
+
+            println!("{}: ok ({} warning(s))", path.display(), warnings.len());
+            0
+        }
+        Err(e) if e.is_not_found() && !explicit => {
+            println!("{}: not found, the defaults will be used", path.display());
+            0
+        }
+        Err(ConfigError::Invalid { errors, warnings }) => {
+            for warning in warnings.iter() {
+                println!("warning: {}", warning);
+            }
+
+            for error in errors.iter() {
+                println!("error: {}", error);
+            }
+
+            println!("{}: {} error(s)", path.display(), errors.len());
+            1
+        }
+        Err(e) => {
+            println!("error: {}", e);
+            println!("{}: invalid", path.display());
+            1
         }
     }
 }
@@ -1,11 +1,19 @@
 use ordered_float::OrderedFloat;
 use crate::ticker::Data;
-use yahoo_finance::{Interval, Profile};
+use yahoo_finance::Profile;
 
+#[derive(Debug)]
 pub enum Message {
-    DataUpdate((String, Data)),
+    /// History answering the request of the given generation, or why it
+    /// could not be loaded.
+    DataUpdate((String, u64, Result<Data, String>)),
     ProfileInit((String, Profile)),
-    PriceUpdate((String, f64)),
-    SetInterval((String, Interval)),
+    /// Streamed quote: symbol, price and day volume.
+    PriceUpdate((String, f64, u64)),
+    /// 52-week low and high.
+    YearRange((String, (f64, f64))),
+    MarketCap((String, f64)),
+    /// An alert command or webhook failed.
+    ActionFailed(String),
     Start,
 }
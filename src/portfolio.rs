@@ -0,0 +1,651 @@
+use crate::{
+    config::is_valid_symbol,
+    ledger::{Book, Kind, Ledger, LotMethod},
+    ticker::Ticker,
+    utils::resolve_path,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    ops::Range,
+    path::Path,
+};
+
+fn default_currency() -> String {
+    "USD".into()
+}
+
+/// A holding as written in the configuration: `cost` is the average cost per share.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Position {
+    symbol: String,
+    shares: f64,
+    cost: f64,
+    #[serde(default = "default_currency")]
+    currency: String,
+}
+
+impl Position {
+    pub fn new(symbol: String, shares: f64, cost: f64, currency: String) -> Self {
+        Self {
+            symbol,
+            shares,
+            cost,
+            currency,
+        }
+    }
+
+    pub fn symbol(&self) -> &String {
+        &self.symbol
+    }
+
+    pub fn shares(&self) -> f64 {
+        self.shares
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn currency(&self) -> &String {
+        &self.currency
+    }
+
+    pub fn cost_basis(&self) -> f64 {
+        self.shares * self.cost
+    }
+
+    /// Invalid values of the position, each with the name of its field.
+    pub fn problems(&self) -> Vec<(&'static str, String)> {
+        let mut problems = vec![];
+
+        if !is_valid_symbol(&self.symbol) {
+            problems.push(("symbol", format!("invalid symbol \"{}\"", self.symbol)));
+        }
+
+        if !self.shares.is_finite() || self.shares == 0.0 {
+            problems.push(("shares", "shares must be a non-zero number".into()));
+        }
+
+        if !self.cost.is_finite() || self.cost < 0.0 {
+            problems.push(("cost", "cost must be a positive number".into()));
+        }
+
+        if self.currency.len() != 3 || !self.currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            problems.push((
+                "currency",
+                format!("invalid currency \"{}\" (expected an ISO code such as USD)", self.currency),
+            ));
+        }
+
+        problems
+    }
+}
+
+/// The `[portfolio]` section of the configuration. Positions are listed
+/// inline, read from the separate TOML `file` or derived from a CSV `ledger`
+/// of transactions using the `lots` matching method.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PortfolioConfig {
+    #[serde(default)]
+    file: Option<String>,
+    #[serde(default)]
+    ledger: Option<String>,
+    #[serde(default)]
+    lots: Option<String>,
+    #[serde(default)]
+    positions: Vec<Position>,
+    /// Custom tags per symbol, e.g. `AAPL = ["core", "us"]`.
+    #[serde(default)]
+    tags: HashMap<String, Vec<String>>,
+    /// Target weights in percent per grouping, e.g. `[portfolio.targets.sector]`.
+    #[serde(default)]
+    targets: HashMap<String, HashMap<String, f64>>,
+}
+
+#[derive(Deserialize)]
+struct PortfolioFile {
+    #[serde(default)]
+    positions: Vec<Position>,
+}
+
+impl PortfolioConfig {
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    pub fn ledger(&self) -> Option<&str> {
+        self.ledger.as_deref()
+    }
+
+    pub fn lots(&self) -> Option<&str> {
+        self.lots.as_deref()
+    }
+
+    pub fn lot_method(&self) -> LotMethod {
+        self.lots().and_then(|l| l.parse().ok()).unwrap_or(LotMethod::Fifo)
+    }
+
+    pub fn positions(&self) -> &Vec<Position> {
+        &self.positions
+    }
+
+    pub fn targets(&self) -> &HashMap<String, HashMap<String, f64>> {
+        &self.targets
+    }
+}
+
+/// Valuation of one position at the latest known price.
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub symbol: String,
+    pub name: String,
+    pub currency: String,
+    pub shares: f64,
+    pub price: f64,
+    pub cost_basis: f64,
+    pub market_value: f64,
+    pub gain: f64,
+    pub gain_pct: f64,
+    pub day_change: f64,
+    pub day_change_pct: f64,
+    /// Share of the total market value in the same currency.
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Totals {
+    pub currency: String,
+    pub cost_basis: f64,
+    pub market_value: f64,
+    pub gain: f64,
+    pub gain_pct: f64,
+    pub day_change: f64,
+    pub day_change_pct: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    pub holdings: Vec<Holding>,
+    /// One entry per currency, in order of first appearance.
+    pub totals: Vec<Totals>,
+}
+
+/// Portfolio value over time, reconstructed from each held ticker's history
+/// and the position changes recorded in the ledger.
+#[derive(Debug, Clone, Default)]
+pub struct ValueHistory {
+    pub currency: String,
+    pub dates: Vec<NaiveDate>,
+    pub value: Vec<f64>,
+    pub cost: Vec<f64>,
+    /// Net money put in on each date: buys and fees are contributions, sales
+    /// and dividends withdrawals.
+    pub flows: Vec<f64>,
+}
+
+impl ValueHistory {
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    /// The days in `range`, clamped to the recorded ones.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        let end = range.end.min(self.dates.len());
+        let range = range.start.min(end)..end;
+
+        Self {
+            currency: self.currency.clone(),
+            dates: self.dates[range.clone()].to_vec(),
+            value: self.value[range.clone()].to_vec(),
+            cost: self.cost[range.clone()].to_vec(),
+            flows: self.flows[range].to_vec(),
+        }
+    }
+
+    pub fn labels(&self) -> Vec<String> {
+        self.dates.iter().map(|d| d.format("%b %e %Y").to_string()).collect()
+    }
+
+    /// Daily returns net of the flows, as chained by `time_weighted_return`.
+    pub fn returns(&self) -> Vec<(NaiveDate, f64)> {
+        (1..self.value.len())
+            .filter(|idx| self.value[idx - 1] + self.flows[*idx] > 0.0)
+            .map(|idx| (self.dates[idx], self.value[idx] / (self.value[idx - 1] + self.flows[idx]) - 1.0))
+            .collect()
+    }
+
+    /// Chain-linked daily returns, with each day's flow assumed to arrive at
+    /// the start of the day.
+    pub fn time_weighted_return(&self) -> Option<f64> {
+        if self.value.len() < 2 {
+            return None;
+        }
+
+        let mut growth = 1.0;
+
+        for idx in 1..self.value.len() {
+            let start = self.value[idx - 1] + self.flows[idx];
+
+            if start > 0.0 {
+                growth *= self.value[idx] / start;
+            }
+        }
+
+        Some(growth - 1.0)
+    }
+
+    /// Annualized internal rate of return of the starting value, the flows and
+    /// the final value.
+    pub fn money_weighted_return(&self) -> Option<f64> {
+        let first = *self.dates.first()?;
+        let last = *self.dates.last()?;
+
+        if (last - first).num_days() <= 0 {
+            return None;
+        }
+
+        let mut cash_flows = vec![(0.0, -self.value[0])];
+
+        for idx in 1..self.dates.len() {
+            let years = (self.dates[idx] - first).num_days() as f64 / 365.0;
+            cash_flows.push((years, -self.flows[idx]));
+        }
+
+        let years = (last - first).num_days() as f64 / 365.0;
+        cash_flows.push((years, *self.value.last()?));
+
+        let npv = |rate: f64| -> f64 {
+            cash_flows.iter().map(|(t, cf)| cf / (1.0 + rate).powf(*t)).sum()
+        };
+
+        let (mut low, mut high) = (-0.9999, 100.0);
+
+        if npv(low).signum() == npv(high).signum() {
+            return None;
+        }
+
+        for _ in 0..200 {
+            let mid = (low + high) / 2.0;
+
+            match npv(mid).signum() == npv(low).signum() {
+                true => low = mid,
+                false => high = mid,
+            }
+        }
+
+        Some((low + high) / 2.0)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Portfolio {
+    positions: Vec<Position>,
+    ledger: Option<Ledger>,
+    book: Option<Book>,
+    tags: HashMap<String, Vec<String>>,
+    targets: HashMap<String, HashMap<String, f64>>,
+}
+
+impl Portfolio {
+    pub fn new(positions: Vec<Position>) -> Self {
+        Self {
+            positions,
+            ledger: None,
+            book: None,
+            tags: HashMap::new(),
+            targets: HashMap::new(),
+        }
+    }
+
+    /// Collects the configured positions, reading `file` relative to `base`
+    /// (the directory of the configuration file) when it is not absolute.
+    pub fn load(conf: &PortfolioConfig, base: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut positions = conf.positions().clone();
+
+        if let Some(file) = conf.file() {
+            let path = resolve_path(file, base);
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| format!("could not read portfolio {}: {}", path.display(), e))?;
+            let parsed: PortfolioFile = toml::from_str(&contents)
+                .map_err(|e| format!("invalid portfolio {}: {}", path.display(), e))?;
+
+            let problems: Vec<String> = parsed.positions
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, p)| p.problems().into_iter().map(move |(_, m)| format!("positions[{}]: {}", idx, m)))
+                .collect();
+
+            if !problems.is_empty() {
+                return Err(format!("invalid portfolio {}: {}", path.display(), problems.join("; ")).into());
+            }
+
+            positions.extend(parsed.positions);
+        }
+
+        for position in positions.iter_mut() {
+            position.symbol = position.symbol.to_uppercase();
+        }
+
+        let mut portfolio = Self::new(positions);
+
+        portfolio.tags = conf.tags.iter().map(|(k, v)| (k.to_uppercase(), v.clone())).collect();
+        portfolio.targets = conf.targets.clone();
+
+        if let Some(file) = conf.ledger() {
+            let path = resolve_path(file, base);
+            let ledger = Ledger::read(&path)?;
+            let book = ledger
+                .derive(conf.lot_method())
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+            portfolio.positions.extend(book.positions());
+            portfolio.ledger = Some(ledger);
+            portfolio.book = Some(book);
+        }
+
+        Ok(portfolio)
+    }
+
+    pub fn ledger(&self) -> Option<&Ledger> {
+        self.ledger.as_ref()
+    }
+
+    pub fn book(&self) -> Option<&Book> {
+        self.book.as_ref()
+    }
+
+    pub fn tags(&self) -> &HashMap<String, Vec<String>> {
+        &self.tags
+    }
+
+    /// Target weights for a grouping key such as `sector`.
+    pub fn targets(&self, grouping: &str) -> Option<&HashMap<String, f64>> {
+        self.targets.get(grouping)
+    }
+
+    /// Currency of the first position; the value history only covers
+    /// positions in this currency as there is no exchange rate data.
+    pub fn base_currency(&self) -> String {
+        self.positions
+            .first()
+            .map(|p| p.currency.clone())
+            .unwrap_or_else(default_currency)
+    }
+
+    /// Reconstructs the daily value and cost basis over the dates loaded for
+    /// the held tickers. Positions from the ledger follow its transactions,
+    /// inline positions are assumed to have been held throughout.
+    pub fn value_history(&self, tickers: &[Ticker]) -> ValueHistory {
+        let currency = self.base_currency();
+        let fixed: Vec<&Position> = self.positions
+            .iter()
+            .filter(|p| p.currency == currency)
+            .filter(|p| self.book.as_ref().and_then(|b| b.holding(&p.symbol)).is_none())
+            .collect();
+
+        let mut symbols: Vec<&str> = fixed.iter().map(|p| p.symbol.as_str()).collect();
+
+        if let Some(book) = &self.book {
+            symbols.extend(book.holdings.iter().filter(|h| h.currency == currency).map(|h| h.symbol.as_str()));
+        }
+
+        let mut prices: HashMap<&str, BTreeMap<NaiveDate, f64>> = HashMap::new();
+        let mut dates = BTreeSet::new();
+
+        for symbol in symbols.iter() {
+            if let Some(ticker) = tickers.iter().find(|t| t.identifier() == symbol) {
+                let data = ticker.data();
+                let closes: BTreeMap<NaiveDate, f64> = data.dates()
+                    .into_iter()
+                    .zip(data.price_data().iter().map(|p| f64::from(*p)))
+                    .collect();
+
+                dates.extend(closes.keys().cloned());
+                prices.insert(symbol, closes);
+            }
+        }
+
+        let price_on = |symbol: &str, date: NaiveDate| -> Option<f64> {
+            prices.get(symbol)?.range(..=date).next_back().map(|(_, p)| *p)
+        };
+
+        let transactions = self.ledger.as_ref().map(|l| l.transactions().as_slice()).unwrap_or_default();
+
+        let mut history = ValueHistory {
+            currency: currency.clone(),
+            ..Default::default()
+        };
+
+        // replayed alongside the dates, as the loaded book only holds the end state
+        let mut book = self.book.as_ref().map(|b| Book::new(b.method));
+        let mut applied = 0;
+
+        for (idx, date) in dates.into_iter().enumerate() {
+            let pending = transactions[applied..].iter().take_while(|t| t.date <= date).count();
+            let mut flow = 0.0;
+
+            for t in transactions[applied..applied + pending].iter() {
+                let quantity = t.quantity.unwrap_or(0.0);
+                let price = t.price.unwrap_or(0.0);
+
+                let amount = match t.kind {
+                    Kind::Buy => quantity * price + t.fee,
+                    Kind::Sell => -(quantity * price - t.fee),
+                    Kind::Dividend => {
+                        let shares = t.quantity
+                            .or_else(|| book.as_ref().and_then(|b| b.holding(&t.symbol)).map(|h| h.shares()))
+                            .unwrap_or(0.0);
+                        -(shares * price - t.fee)
+                    }
+                    Kind::Fee if t.fee == 0.0 => price,
+                    Kind::Fee => t.fee,
+                    Kind::Split => 0.0,
+                };
+
+                if t.currency == currency {
+                    flow += amount;
+                }
+
+                // the whole ledger was derived when loading, so this cannot fail
+                if let Some(book) = book.as_mut() {
+                    let _ = book.apply(t);
+                }
+            }
+
+            applied += pending;
+
+            let mut value = 0.0;
+            let mut cost = 0.0;
+
+            for position in fixed.iter() {
+                value += position.shares * price_on(&position.symbol, date).unwrap_or(position.cost);
+                cost += position.cost_basis();
+            }
+
+            for holding in book.iter().flat_map(|b| b.holdings.iter()).filter(|h| h.currency == currency) {
+                let shares = holding.shares();
+                let average = holding.cost_basis() / shares.max(f64::EPSILON);
+
+                value += shares * price_on(&holding.symbol, date).unwrap_or(average);
+                cost += holding.cost_basis();
+            }
+
+            history.dates.push(date);
+            history.value.push(value);
+            history.cost.push(cost);
+            // flows before the first date are part of the starting value
+            history.flows.push(match idx {
+                0 => 0.0,
+                _ => flow,
+            });
+        }
+
+        history
+    }
+
+    pub fn positions(&self) -> &Vec<Position> {
+        &self.positions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Symbols of the positions and of every ledger holding, closed ones
+    /// included, as the value history needs their prices too.
+    pub fn symbols(&self) -> Vec<&str> {
+        let mut symbols: Vec<&str> = vec![];
+        let ledger = self.book.iter().flat_map(|b| b.holdings.iter().map(|h| &h.symbol));
+
+        for symbol in self.positions.iter().map(|p| &p.symbol).chain(ledger) {
+            if !symbols.contains(&symbol.as_str()) {
+                symbols.push(symbol);
+            }
+        }
+
+        symbols
+    }
+
+    /// Values every position with the matching ticker's latest price. Positions
+    /// without a loaded price are valued at cost so the totals stay meaningful.
+    pub fn summarize(&self, tickers: &[Ticker]) -> Summary {
+        let mut holdings = vec![];
+
+        for position in self.positions.iter() {
+            let ticker = tickers.iter().find(|t| t.identifier() == position.symbol());
+
+            let (name, price, previous) = match ticker {
+                Some(t) if t.realtime_price() > 0.0 => {
+                    let previous = t.previous_close().unwrap_or_else(|| t.realtime_price());
+                    (t.info().name().clone(), t.realtime_price(), previous)
+                }
+                Some(t) => (t.info().name().clone(), position.cost, position.cost),
+                None => (String::new(), position.cost, position.cost),
+            };
+
+            let cost_basis = position.cost_basis();
+            let market_value = position.shares * price;
+            let day_change = position.shares * (price - previous);
+
+            holdings.push(Holding {
+                symbol: position.symbol.clone(),
+                name,
+                currency: position.currency.clone(),
+                shares: position.shares,
+                price,
+                cost_basis,
+                market_value,
+                gain: market_value - cost_basis,
+                gain_pct: percent(market_value - cost_basis, cost_basis),
+                day_change,
+                day_change_pct: percent(day_change, market_value - day_change),
+                weight: 0.0,
+            });
+        }
+
+        let mut totals: Vec<Totals> = vec![];
+
+        for holding in holdings.iter() {
+            let idx = match totals.iter().position(|t| t.currency == holding.currency) {
+                Some(idx) => idx,
+                None => {
+                    totals.push(Totals {
+                        currency: holding.currency.clone(),
+                        cost_basis: 0.0,
+                        market_value: 0.0,
+                        gain: 0.0,
+                        gain_pct: 0.0,
+                        day_change: 0.0,
+                        day_change_pct: 0.0,
+                    });
+                    totals.len() - 1
+                }
+            };
+
+            let total = &mut totals[idx];
+            total.cost_basis += holding.cost_basis;
+            total.market_value += holding.market_value;
+            total.gain += holding.gain;
+            total.day_change += holding.day_change;
+        }
+
+        for total in totals.iter_mut() {
+            total.gain_pct = percent(total.gain, total.cost_basis);
+            total.day_change_pct = percent(total.day_change, total.market_value - total.day_change);
+        }
+
+        for holding in holdings.iter_mut() {
+            if let Some(total) = totals.iter().find(|t| t.currency == holding.currency) {
+                holding.weight = percent(holding.market_value, total.market_value);
+            }
+        }
+
+        Summary { holdings, totals }
+    }
+}
+
+fn percent(value: f64, base: f64) -> f64 {
+    match base == 0.0 {
+        true => 0.0,
+        false => value / base * 100.0,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticker::Data;
+    use ordered_float::OrderedFloat;
+
+    /// A portfolio holding only what `rows` of a ledger describe.
+    fn portfolio(rows: &str) -> Portfolio {
+        let ledger = Ledger::parse(&format!("date,type,symbol,quantity,price,fee\n{}", rows)).unwrap();
+        let book = ledger.derive(LotMethod::Fifo).unwrap();
+
+        let mut portfolio = Portfolio::new(book.positions());
+        portfolio.ledger = Some(ledger);
+        portfolio.book = Some(book);
+        portfolio
+    }
+
+    /// A ticker with one close per `(day of January 2021, close)`.
+    fn ticker(symbol: &str, closes: &[(u32, f64)]) -> Ticker {
+        let timestamp = |day: u32| NaiveDate::from_ymd_opt(2021, 1, day).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+
+        let mut ticker = Ticker::new(symbol.into());
+        ticker.set_data(Data::new(
+            closes.iter().map(|(_, p)| OrderedFloat::from(*p)).collect(),
+            closes.iter().map(|(d, _)| d.to_string()).collect(),
+            vec![0; closes.len()],
+            closes.iter().map(|(d, _)| timestamp(*d)).collect(),
+        ));
+        ticker
+    }
+
+    #[test]
+    fn symbols_include_closed_holdings() {
+        let portfolio = portfolio("2021-01-04,buy,AAPL,10,100,0\n2021-01-06,sell,AAPL,10,120,0\n2021-01-05,buy,MSFT,1,200,0\n");
+
+        assert_eq!(portfolio.positions().len(), 1);
+        assert_eq!(portfolio.symbols(), vec!["MSFT", "AAPL"]);
+    }
+
+    #[test]
+    fn value_history_prices_closed_holdings() {
+        let portfolio = portfolio("2021-01-04,buy,AAPL,10,100,0\n2021-01-06,sell,AAPL,10,120,0\n2021-01-05,buy,MSFT,1,200,0\n");
+        let tickers = vec![
+            ticker("AAPL", &[(4, 100.0), (5, 110.0), (6, 120.0), (7, 130.0)]),
+            ticker("MSFT", &[(4, 190.0), (5, 200.0), (6, 210.0), (7, 220.0)]),
+        ];
+
+        let history = portfolio.value_history(&tickers);
+
+        assert_eq!(history.value, vec![1000.0, 1300.0, 210.0, 220.0]);
+        assert_eq!(history.cost, vec![1000.0, 1200.0, 200.0, 200.0]);
+        assert_eq!(history.flows, vec![0.0, 200.0, -1200.0, 0.0]);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{range::DateRange, ticker::Data};
+use chrono::{Duration, TimeZone, Utc};
+use futures::{future, StreamExt};
+use std::{fmt, str::FromStr};
+use yahoo_finance::{history, Interval, Profile, Quote, Streamer};
+
+/// Source of market data used by both the TUI and the headless commands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Provider {
+    Yahoo,
+}
+
+impl FromStr for Provider {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "yahoo" => Ok(Provider::Yahoo),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Provider::Yahoo => write!(f, "yahoo"),
+        }
+    }
+}
+
+impl Provider {
+    pub async fn history(&self, symbol: &str, interval: Interval) -> Result<Data, yahoo_finance::Error> {
+        match self {
+            Provider::Yahoo => {
+                let hist = history::retrieve_interval(symbol, interval).await?;
+                Ok(Data::from_bars(&hist))
+            }
+        }
+    }
+
+    /// Daily bars from the start of `range` through its end, or up to now.
+    pub async fn history_range(&self, symbol: &str, range: DateRange) -> Result<Data, yahoo_finance::Error> {
+        let midnight = |date: chrono::NaiveDate| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default());
+
+        match self {
+            Provider::Yahoo => {
+                let end = range.end().map(|end| midnight(end + Duration::days(1)));
+                let hist = history::retrieve_range(symbol, midnight(range.start()), end).await?;
+                Ok(Data::from_bars(&hist))
+            }
+        }
+    }
+
+    pub async fn profile(&self, symbol: &str) -> Result<Profile, yahoo_finance::Error> {
+        match self {
+            Provider::Yahoo => Profile::load(symbol).await,
+        }
+    }
+
+    /// Market capitalisation from the quote endpoint, as the profile has none.
+    /// `None` where Yahoo has no value, e.g. for indices and currencies.
+    pub async fn market_cap(&self, symbol: &str) -> Result<Option<f64>, reqwest::Error> {
+        match self {
+            Provider::Yahoo => {
+                let url = reqwest::Url::parse_with_params(
+                    "https://query1.finance.yahoo.com/v7/finance/quote",
+                    &[("symbols", symbol), ("fields", "marketCap")],
+                ).expect("valid quote URL");
+
+                let body: serde_json::Value = reqwest::get(url).await?.error_for_status()?.json().await?;
+                Ok(body["quoteResponse"]["result"][0]["marketCap"].as_f64())
+            }
+        }
+    }
+
+    /// Streams realtime quotes of `symbols`, calling `on_quote` for each until
+    /// it returns false or the connection closes.
+    pub async fn stream<F>(&self, symbols: Vec<String>, mut on_quote: F)
+    where
+        F: FnMut(&Quote) -> bool
+    {
+        match self {
+            Provider::Yahoo => {
+                let streamer = Streamer::new(symbols.iter().map(|s| s.as_str()).collect());
+
+                streamer.stream().await
+                    .take_while(move |quote| future::ready(on_quote(quote)))
+                    .for_each(|_| future::ready(()))
+                    .await;
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{cli::Format, provider::Provider, ticker::Ticker};
+use chrono::{Local, TimeZone};
+use futures::future;
+use serde::Serialize;
+use yahoo_finance::{Interval, TradingSession};
+
+/// Snapshot of a symbol printed by `tuinance quote`.
+#[derive(Serialize, Debug, Clone)]
+pub struct Quote {
+    pub symbol: String,
+    pub name: String,
+    pub price: f64,
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    pub volume: Option<u64>,
+}
+
+impl From<&Ticker> for Quote {
+    fn from(ticker: &Ticker) -> Self {
+        let price = ticker.realtime_price();
+        let change = ticker.previous_close().map(|close| price - close);
+
+        let change_percent = ticker
+            .previous_close()
+            .filter(|close| *close != 0.0)
+            .map(|close| (price - close) / close * 100.0);
+
+        Self {
+            symbol: ticker.identifier().clone(),
+            name: ticker.info().name().clone(),
+            price,
+            change,
+            change_percent,
+            volume: ticker.volume_data().last().copied(),
+        }
+    }
+}
+
+/// Loads the last few daily bars and the profile of `symbol`. A missing
+/// profile is not an error, a symbol without bars is.
+pub async fn load(provider: Provider, symbol: &str) -> Result<Ticker, String> {
+    let mut ticker = Ticker::new(symbol.to_string());
+    ticker.set_interval(Interval::_5d);
+
+    let (data, profile) = future::join(
+        provider.history(symbol, Interval::_5d),
+        provider.profile(symbol),
+    ).await;
+
+    match data {
+        Ok(data) if !data.is_empty() => ticker.set_data(data),
+        Ok(_) => return Err(format!("unknown symbol: {}", symbol)),
+        Err(e) => return Err(format!("could not fetch {}: {}", symbol, e)),
+    }
+
+    if let Ok(profile) = profile {
+        ticker.init_info(profile);
+    }
+
+    Ok(ticker)
+}
+
+/// Fetches every symbol concurrently, keeping the order of `symbols`.
+pub async fn fetch(provider: Provider, symbols: &[String]) -> Vec<Result<Quote, String>> {
+    future::join_all(symbols.iter().map(|symbol| async move {
+        load(provider, symbol).await.map(|ticker| Quote::from(&ticker))
+    })).await
+}
+
+pub fn format(quotes: &[Quote], format: Format) -> String {
+    match format {
+        Format::Json => serde_json::to_string_pretty(quotes).unwrap_or_default() + "\n",
+        Format::Jsonl => quotes
+            .iter()
+            .map(|q| serde_json::to_string(q).unwrap_or_default() + "\n")
+            .collect(),
+        Format::Csv => {
+            let mut out = String::from("symbol,name,price,change,change_percent,volume\n");
+
+            for q in quotes.iter() {
+                out += &format!(
+                    "{},{},{},{},{},{}\n",
+                    q.symbol,
+                    csv_field(&q.name),
+                    q.price,
+                    q.change.map(|c| c.to_string()).unwrap_or_default(),
+                    q.change_percent.map(|c| c.to_string()).unwrap_or_default(),
+                    q.volume.map(|v| v.to_string()).unwrap_or_default(),
+                );
+            }
+
+            out
+        }
+        Format::Table => {
+            let rows: Vec<Vec<String>> = quotes.iter().map(|q| vec![
+                q.symbol.clone(),
+                q.name.clone(),
+                format!("{:.2}", q.price),
+                q.change.map(|c| format!("{:+.2}", c)).unwrap_or_else(|| "-".into()),
+                q.change_percent.map(|c| format!("{:+.2}%", c)).unwrap_or_else(|| "-".into()),
+                q.volume.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+            ]).collect();
+
+            table(&["SYMBOL", "NAME", "LAST", "CHANGE", "CHANGE%", "VOLUME"], &rows, 2)
+        }
+    }
+}
+
+/// Streamed quote printed by `tuinance watch`.
+#[derive(Serialize, Debug, Clone)]
+pub struct Tick {
+    pub symbol: String,
+    pub price: f64,
+    /// Day volume.
+    pub volume: u64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    /// RFC 3339 local time of the quote.
+    pub time: String,
+    /// `pre`, `regular`, `post` or `other`.
+    pub session: &'static str,
+}
+
+impl From<&yahoo_finance::Quote> for Tick {
+    fn from(quote: &yahoo_finance::Quote) -> Self {
+        let session = match quote.session {
+            TradingSession::PreMarket => "pre",
+            TradingSession::Regular => "regular",
+            TradingSession::AfterHours => "post",
+            TradingSession::Other => "other",
+        };
+
+        let time = Local
+            .timestamp_millis_opt(quote.timestamp)
+            .single()
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+
+        Self {
+            symbol: quote.symbol.clone(),
+            price: quote.price,
+            volume: quote.volume,
+            timestamp: quote.timestamp,
+            time,
+            session,
+        }
+    }
+}
+
+/// Header printed before the first tick, if the format has one.
+pub fn tick_header(format: Format) -> Option<&'static str> {
+    match format {
+        Format::Csv => Some("symbol,price,volume,timestamp,time,session\n"),
+        _ => None,
+    }
+}
+
+/// Formats a tick as a single line. `Json` is written as JSON lines too, as
+/// the stream has no end to close an array at.
+pub fn format_tick(tick: &Tick, format: Format) -> String {
+    match format {
+        Format::Json | Format::Jsonl => serde_json::to_string(tick).unwrap_or_default() + "\n",
+        Format::Csv => format!(
+            "{},{},{},{},{},{}\n",
+            tick.symbol, tick.price, tick.volume, tick.timestamp, tick.time, tick.session
+        ),
+        Format::Table => format!(
+            "{}  {:<8} {:>12.4} {:>14}  {}\n",
+            tick.time, tick.symbol, tick.price, tick.volume, tick.session
+        ),
+    }
+}
+
+/// Aligns `rows` under `header`, the first `left` columns to the left and the
+/// remaining (numeric) ones to the right.
+pub fn table(header: &[&str], rows: &[Vec<String>], left: usize) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        let cells: Vec<String> = cells.iter().enumerate().map(|(idx, cell)| match idx < left {
+            true => format!("{:<width$}", cell, width = widths[idx]),
+            false => format!("{:>width$}", cell, width = widths[idx]),
+        }).collect();
+
+        cells.join("  ").trim_end().to_string() + "\n"
+    };
+
+    let mut out = line(header.to_vec());
+
+    for row in rows.iter() {
+        out += &line(row.iter().map(|c| c.as_str()).collect());
+    }
+
+    out
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+pub fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotes() -> Vec<Quote> {
+        vec![
+            Quote {
+                symbol: "AAPL".into(),
+                name: "Apple Inc.".into(),
+                price: 190.5,
+                change: Some(1.25),
+                change_percent: Some(0.66),
+                volume: Some(1000),
+            },
+            Quote {
+                symbol: "BRK-B".into(),
+                name: "Berkshire Hathaway, \"B\"".into(),
+                price: 400.0,
+                change: None,
+                change_percent: None,
+                volume: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn csv_field_quotes_when_needed() {
+        assert_eq!(csv_field("Apple Inc."), "Apple Inc.");
+        assert_eq!(csv_field("Berkshire Hathaway, Inc."), "\"Berkshire Hathaway, Inc.\"");
+        assert_eq!(csv_field("The \"Fund\""), "\"The \"\"Fund\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn format_table() {
+        assert_eq!(format(&quotes(), Format::Table), [
+            "SYMBOL  NAME                       LAST  CHANGE  CHANGE%  VOLUME\n",
+            "AAPL    Apple Inc.               190.50   +1.25   +0.66%    1000\n",
+            "BRK-B   Berkshire Hathaway, \"B\"  400.00       -        -       -\n",
+        ].concat());
+    }
+
+    #[test]
+    fn format_csv() {
+        assert_eq!(format(&quotes(), Format::Csv), [
+            "symbol,name,price,change,change_percent,volume\n",
+            "AAPL,Apple Inc.,190.5,1.25,0.66,1000\n",
+            "BRK-B,\"Berkshire Hathaway, \"\"B\"\"\",400,,,\n",
+        ].concat());
+    }
+
+    #[test]
+    fn format_json() {
+        let json: serde_json::Value = serde_json::from_str(&format(&quotes(), Format::Json)).unwrap();
+
+        assert_eq!(json[0]["symbol"], "AAPL");
+        assert_eq!(json[0]["change_percent"], 0.66);
+        assert_eq!(json[1]["name"], "Berkshire Hathaway, \"B\"");
+        assert!(json[1]["volume"].is_null());
+
+        let jsonl = format(&quotes(), Format::Jsonl);
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"symbol":"AAPL","name":"Apple Inc.","price":190.5,"change":1.25,"change_percent":0.66,"volume":1000}"#);
+        assert!(format(&[], Format::Jsonl).is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// A custom charted period: from `start` to `end` inclusive, or up to today.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    start: NaiveDate,
+    end: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub const SYNTAX: &'static str = "YYYY-MM-DD..YYYY-MM-DD, YYYY-MM-DD.. or -90d, -12w, -6m, -2y";
+
+    pub fn new(start: NaiveDate, end: Option<NaiveDate>) -> Result<Self, String> {
+        let today = Local::now().naive_local().date();
+
+        if start > today {
+            return Err(format!("{} is in the future", start));
+        }
+
+        match end {
+            Some(end) if end < start => Err(format!("{} is before {}", end, start)),
+            _ => Ok(Self { start, end }),
+        }
+    }
+
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    pub fn end(&self) -> Option<NaiveDate> {
+        self.end
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "{} to {}", self.start, end),
+            None => write!(f, "since {}", self.start),
+        }
+    }
+}
+
+/// Parses `2020-03-01..2021-01-01`, an open-ended `2020-03-01..`, or a period
+/// back from today such as `-90d`, `-12w`, `-6m` or `-2y`.
+impl FromStr for DateRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let date = |s: &str| {
+            NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| format!("invalid date \"{}\" (expected YYYY-MM-DD)", s.trim()))
+        };
+
+        if let Some((start, end)) = s.split_once("..") {
+            let end = match end.trim().is_empty() {
+                true => None,
+                false => Some(date(end)?),
+            };
+
+            return Self::new(date(start)?, end);
+        }
+
+        let period = s.strip_prefix('-').ok_or_else(|| format!("invalid range \"{}\" (expected {})", s, Self::SYNTAX))?;
+        let (count, unit) = period.split_at(period.find(|c: char| !c.is_ascii_digit()).unwrap_or(period.len()));
+
+        let count: u32 = count.parse().map_err(|_| format!("invalid range \"{}\" (expected {})", s, Self::SYNTAX))?;
+        let today = Local::now().naive_local().date();
+
+        let start = match unit.to_lowercase().as_str() {
+            "d" => today.checked_sub_signed(Duration::days(count as i64)),
+            "w" => today.checked_sub_signed(Duration::weeks(count as i64)),
+            "m" | "mo" => months_before(today, count),
+            "y" => count.checked_mul(12).and_then(|months| months_before(today, months)),
+            _ => return Err(format!("invalid unit \"{}\" (expected d, w, m or y)", unit)),
+        };
+
+        Self::new(start.ok_or("range too large")?, None)
+    }
+}
+
+/// The same day `months` earlier, or the last day of that month if shorter.
+/// `None` when that is before the earliest representable date.
+fn months_before(date: NaiveDate, months: u32) -> Option<NaiveDate> {
+    let total = date.year() as i64 * 12 + date.month0() as i64 - months as i64;
+    let year = i32::try_from(total.div_euclid(12)).ok()?;
+    let month = total.rem_euclid(12) as u32 + 1;
+
+    (1..=date.day())
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn today() -> NaiveDate {
+        Local::now().naive_local().date()
+    }
+
+    #[test]
+    fn parses_absolute_ranges() {
+        let range: DateRange = "2020-03-01..2021-01-01".parse().unwrap();
+        assert_eq!((range.start(), range.end()), (ymd(2020, 3, 1), Some(ymd(2021, 1, 1))));
+
+        let range: DateRange = " 2020-03-01 .. ".parse().unwrap();
+        assert_eq!((range.start(), range.end()), (ymd(2020, 3, 1), None));
+    }
+
+    #[test]
+    fn rejects_invalid_absolute_ranges() {
+        assert!("2021-01-01..2020-01-01".parse::<DateRange>().is_err());
+        assert!("2020-3-x..".parse::<DateRange>().is_err());
+        assert!("2020-01-01..soon".parse::<DateRange>().is_err());
+
+        let future = format!("{}..", today() + Duration::days(2));
+        assert!(future.parse::<DateRange>().is_err());
+    }
+
+    #[test]
+    fn parses_relative_ranges() {
+        let start = |s: &str| s.parse::<DateRange>().map(|r| (r.start(), r.end()));
+
+        assert_eq!(start("-90d"), Ok((today() - Duration::days(90), None)));
+        assert_eq!(start("-12w"), Ok((today() - Duration::weeks(12), None)));
+        assert_eq!(start("-6m"), Ok((months_before(today(), 6).unwrap(), None)));
+        assert_eq!(start("-1MO"), Ok((months_before(today(), 1).unwrap(), None)));
+        assert_eq!(start("-2y"), Ok((months_before(today(), 24).unwrap(), None)));
+        assert_eq!(start("-0d"), Ok((today(), None)));
+    }
+
+    #[test]
+    fn rejects_invalid_relative_ranges() {
+        for s in &["90d", "-d", "-9x", "-", "", "-1.5y"] {
+            assert!(s.parse::<DateRange>().is_err(), "{} parsed", s);
+        }
+    }
+
+    #[test]
+    fn rejects_ranges_too_large_without_panicking() {
+        for s in &["-100000000d", "-100000000w", "-4000000000m", "-400000000y", "-200000000y"] {
+            assert_eq!(s.parse::<DateRange>(), Err("range too large".to_string()), "{}", s);
+        }
+    }
+
+    #[test]
+    fn months_before_clamps_to_month_end() {
+        assert_eq!(months_before(ymd(2021, 3, 31), 1), Some(ymd(2021, 2, 28)));
+        assert_eq!(months_before(ymd(2020, 3, 31), 1), Some(ymd(2020, 2, 29)));
+        assert_eq!(months_before(ymd(2020, 1, 15), 2), Some(ymd(2019, 11, 15)));
+        assert_eq!(months_before(ymd(2020, 2, 29), 12), Some(ymd(2019, 2, 28)));
+        assert_eq!(months_before(ymd(2020, 1, 15), u32::MAX), None);
+    }
+}
@@ -0,0 +1,55 @@
+use crate::store;
+use futures::future::{AbortHandle, AbortRegistration};
+use std::collections::HashMap;
+
+/// History requests in flight, at most one per symbol. Each is tagged with a
+/// generation, so a response that raced its cancellation is still recognized
+/// as stale.
+#[derive(Debug, Default)]
+pub struct Requests {
+    generation: u64,
+    in_flight: HashMap<String, AbortHandle>,
+}
+
+impl Requests {
+    /// Aborts the request still in flight for `symbol` and returns the
+    /// generation of the next one, with the registration to make it abortable
+    /// (see `futures::future::Abortable`).
+    pub fn issue(&mut self, symbol: &str) -> (u64, AbortRegistration) {
+        let (handle, registration) = AbortHandle::new_pair();
+
+        if let Some(previous) = self.in_flight.insert(store::normalize(symbol), handle) {
+            previous.abort();
+        }
+
+        self.generation += 1;
+        (self.generation, registration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, future::{self, Abortable, Aborted}};
+
+    #[test]
+    fn issue_increments_the_generation() {
+        let mut requests = Requests::default();
+
+        assert_eq!(requests.issue("AAPL").0, 1);
+        assert_eq!(requests.issue("MSFT").0, 2);
+        assert_eq!(requests.issue("AAPL").0, 3);
+    }
+
+    #[test]
+    fn issue_aborts_the_previous_request() {
+        let mut requests = Requests::default();
+        let (_, first) = requests.issue("AAPL");
+        let (_, other) = requests.issue("MSFT");
+        let (_, second) = requests.issue("aapl");
+
+        assert_eq!(block_on(Abortable::new(future::ready(()), first)), Err(Aborted));
+        assert_eq!(block_on(Abortable::new(future::ready(()), other)), Ok(()));
+        assert_eq!(block_on(Abortable::new(future::ready(()), second)), Ok(()));
+    }
+}
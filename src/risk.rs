@@ -0,0 +1,258 @@
+use crate::{
+    cli::Format,
+    quote::{csv_field, table},
+    stats::TRADING_DAYS,
+    ticker::Data,
+};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Dated daily returns, as fractions.
+pub type Returns = Vec<(NaiveDate, f64)>;
+
+/// Close-to-close returns of the bars.
+pub fn returns(data: &Data) -> Returns {
+    let dates = data.dates();
+    let closes: Vec<f64> = data.price_data().iter().map(|p| f64::from(*p)).collect();
+
+    (1..closes.len())
+        .filter(|idx| closes[idx - 1] != 0.0)
+        .map(|idx| (dates[idx], closes[idx] / closes[idx - 1] - 1.0))
+        .collect()
+}
+
+/// Pairs up the returns of both series on the dates they have in common.
+pub fn align(a: &[(NaiveDate, f64)], b: &[(NaiveDate, f64)]) -> Vec<(NaiveDate, (f64, f64))> {
+    let b: HashMap<NaiveDate, f64> = b.iter().cloned().collect();
+    a.iter().filter_map(|(date, r)| b.get(date).map(|m| (*date, (*r, *m)))).collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+/// Sample covariance; the variance when both series are the same.
+fn covariance(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+
+    let (a, b): (Vec<f64>, Vec<f64>) = pairs.iter().cloned().unzip();
+    let (mean_a, mean_b) = (mean(&a), mean(&b));
+
+    Some(pairs.iter().map(|(x, y)| (x - mean_a) * (y - mean_b)).sum::<f64>() / (pairs.len() - 1) as f64)
+}
+
+/// Pearson correlation of paired returns.
+pub fn correlation(pairs: &[(f64, f64)]) -> Option<f64> {
+    let var_a = covariance(&pairs.iter().map(|(a, _)| (*a, *a)).collect::<Vec<_>>())?;
+    let var_b = covariance(&pairs.iter().map(|(_, b)| (*b, *b)).collect::<Vec<_>>())?;
+
+    match var_a > 0.0 && var_b > 0.0 {
+        true => Some(covariance(pairs)? / (var_a * var_b).sqrt()),
+        false => None,
+    }
+}
+
+/// Risk-adjusted performance over the charted interval. Ratios and alpha are
+/// annualized from daily returns.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Risk {
+    pub beta: Option<f64>,
+    /// Jensen's alpha.
+    pub alpha: Option<f64>,
+    pub correlation: Option<f64>,
+    pub sharpe: Option<f64>,
+    pub sortino: Option<f64>,
+    /// Number of daily returns used.
+    pub observations: usize,
+}
+
+impl Risk {
+    /// `risk_free_rate` is annual, as a fraction.
+    pub fn compute(returns: &[(NaiveDate, f64)], benchmark: Option<&[(NaiveDate, f64)]>, risk_free_rate: f64) -> Self {
+        let daily_rf = risk_free_rate / TRADING_DAYS;
+        let excess: Vec<f64> = returns.iter().map(|(_, r)| r - daily_rf).collect();
+
+        let sd = covariance(&excess.iter().map(|e| (*e, *e)).collect::<Vec<_>>()).map(f64::sqrt);
+        let sharpe = sd.filter(|sd| *sd > 0.0).map(|sd| mean(&excess) / sd * TRADING_DAYS.sqrt());
+
+        let downside = excess.iter().map(|e| e.min(0.0).powi(2)).sum::<f64>() / excess.len().max(1) as f64;
+        let sortino = match downside > 0.0 {
+            true => Some(mean(&excess) / downside.sqrt() * TRADING_DAYS.sqrt()),
+            false => None,
+        };
+
+        let mut risk = Risk {
+            sharpe,
+            sortino,
+            observations: returns.len(),
+            ..Default::default()
+        };
+
+        if let Some(benchmark) = benchmark {
+            let pairs: Vec<(f64, f64)> = align(returns, benchmark).into_iter().map(|(_, pair)| pair).collect();
+            let market_var = covariance(&pairs.iter().map(|(_, m)| (*m, *m)).collect::<Vec<_>>());
+
+            risk.beta = match (covariance(&pairs), market_var) {
+                (Some(cov), Some(var)) if var > 0.0 => Some(cov / var),
+                _ => None,
+            };
+
+            risk.alpha = risk.beta.map(|beta| {
+                let (asset, market): (Vec<f64>, Vec<f64>) = pairs.iter().cloned().unzip();
+                ((mean(&asset) - daily_rf) - beta * (mean(&market) - daily_rf)) * TRADING_DAYS
+            });
+
+            risk.correlation = correlation(&pairs);
+        }
+
+        risk
+    }
+}
+
+/// A row of `tuinance risk`.
+#[derive(Serialize, Debug, Clone)]
+pub struct Report {
+    pub symbol: String,
+    pub benchmark: Option<String>,
+    #[serde(flatten)]
+    pub risk: Risk,
+}
+
+pub fn format(reports: &[Report], format: Format) -> String {
+    let number = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+
+    match format {
+        Format::Json => serde_json::to_string_pretty(reports).unwrap_or_default() + "\n",
+        Format::Jsonl => reports
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap_or_default() + "\n")
+            .collect(),
+        Format::Csv => {
+            let mut out = String::from("symbol,benchmark,beta,alpha,correlation,sharpe,sortino,observations\n");
+
+            for r in reports.iter() {
+                out += &format!(
+                    "{},{},{},{},{},{},{},{}\n",
+                    csv_field(&r.symbol),
+                    r.benchmark.clone().unwrap_or_default(),
+                    number(r.risk.beta),
+                    number(r.risk.alpha),
+                    number(r.risk.correlation),
+                    number(r.risk.sharpe),
+                    number(r.risk.sortino),
+                    r.risk.observations,
+                );
+            }
+
+            out
+        }
+        Format::Table => {
+            let cell = |v: Option<f64>| v.map(|v| format!("{:.3}", v)).unwrap_or_else(|| "-".into());
+
+            let rows: Vec<Vec<String>> = reports.iter().map(|r| vec![
+                r.symbol.clone(),
+                r.benchmark.clone().unwrap_or_else(|| "-".into()),
+                cell(r.risk.beta),
+                r.risk.alpha.map(|a| format!("{:+.2}%", a * 100.0)).unwrap_or_else(|| "-".into()),
+                cell(r.risk.correlation),
+                cell(r.risk.sharpe),
+                cell(r.risk.sortino),
+                r.risk.observations.to_string(),
+            ]).collect();
+
+            table(&["SYMBOL", "BENCHMARK", "BETA", "ALPHA", "CORR", "SHARPE", "SORTINO", "DAYS"], &rows, 2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(n: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, n).unwrap()
+    }
+
+    fn series(returns: &[f64]) -> Returns {
+        returns.iter().enumerate().map(|(idx, r)| (day(idx as u32 + 1), *r)).collect()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn covariance_needs_two_pairs() {
+        assert_eq!(covariance(&[]), None);
+        assert_eq!(covariance(&[(1.0, 2.0)]), None);
+        assert!(close(covariance(&[(1.0, 2.0), (3.0, 6.0)]).unwrap(), 4.0));
+        assert!(close(covariance(&[(1.0, 1.0), (3.0, 3.0)]).unwrap(), 2.0));
+    }
+
+    #[test]
+    fn correlation_of_a_constant_series_is_undefined() {
+        assert_eq!(correlation(&[(0.01, 0.02), (0.01, -0.01), (0.01, 0.03)]), None);
+        assert!(close(correlation(&[(0.01, 0.02), (0.02, 0.04), (0.03, 0.06)]).unwrap(), 1.0));
+        assert!(close(correlation(&[(0.01, -0.02), (0.02, -0.04), (0.03, -0.06)]).unwrap(), -1.0));
+    }
+
+    #[test]
+    fn align_keeps_common_dates() {
+        let a = vec![(day(1), 0.1), (day(2), 0.2), (day(4), 0.4)];
+        let b = vec![(day(2), -0.2), (day(3), -0.3), (day(4), -0.4)];
+
+        assert_eq!(align(&a, &b), vec![(day(2), (0.2, -0.2)), (day(4), (0.4, -0.4))]);
+        assert_eq!(align(&a, &[]), vec![]);
+    }
+
+    #[test]
+    fn compute_a_constant_series() {
+        let returns = series(&[0.01; 5]);
+        let benchmark = series(&[0.02, -0.01, 0.03, 0.0, 0.01]);
+        let risk = Risk::compute(&returns, Some(&benchmark), 0.0);
+
+        assert_eq!(risk.sharpe, None);
+        assert_eq!(risk.sortino, None);
+        assert!(close(risk.beta.unwrap(), 0.0));
+        assert_eq!(risk.correlation, None);
+        assert_eq!(risk.observations, 5);
+
+        let risk = Risk::compute(&benchmark, Some(&returns), 0.0);
+        assert_eq!(risk.beta, None);
+        assert_eq!(risk.alpha, None);
+    }
+
+    #[test]
+    fn compute_against_itself() {
+        let returns = series(&[0.02, -0.01, 0.03, 0.0, 0.01]);
+        let risk = Risk::compute(&returns, Some(&returns), 0.05);
+
+        assert!(close(risk.beta.unwrap(), 1.0));
+        assert!(close(risk.alpha.unwrap(), 0.0));
+        assert!(close(risk.correlation.unwrap(), 1.0));
+        assert!(risk.sharpe.unwrap() > 0.0);
+        assert!(risk.sortino.is_some());
+    }
+
+    #[test]
+    fn compute_on_misaligned_dates() {
+        let returns = series(&[0.02, -0.01, 0.03, 0.0, 0.01]);
+        let shifted: Returns = returns.iter().map(|(d, r)| (*d + chrono::Duration::days(10), *r)).collect();
+        let risk = Risk::compute(&returns, Some(&shifted), 0.0);
+
+        assert_eq!(risk.beta, None);
+        assert_eq!(risk.correlation, None);
+        assert_eq!(risk.observations, 5);
+
+        // only the three common dates are compared, and they move alike
+        let partial: Returns = returns[2..].iter().map(|(d, r)| (*d, r * 2.0)).collect();
+        let risk = Risk::compute(&returns, Some(&partial), 0.0);
+
+        assert!(close(risk.beta.unwrap(), 0.5));
+        assert!(close(risk.correlation.unwrap(), 1.0));
+        assert!(Risk::compute(&returns, None, 0.0).beta.is_none());
+    }
+}
@@ -0,0 +1,316 @@
+use crate::ui::theme::{rgb, DEFAULT_BG, DEFAULT_FG};
+use std::{fmt::Write as _, fs, io, path::Path};
+use tui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+};
+
+/// Size of a terminal cell in the SVG output, in pixels.
+const CELL_WIDTH: f64 = 8.4;
+const CELL_HEIGHT: f64 = 17.0;
+const FONT_SIZE: f64 = 14.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotFormat {
+    Svg,
+    /// Text with ANSI color escape sequences, for `cat` or `less -R`.
+    Ansi,
+    Text,
+    /// The SVG rasterised to an image, only with the `png` feature.
+    Png,
+}
+
+impl SnapshotFormat {
+    /// Picks the format from the extension: `.svg`, `.ansi`, `.txt` or `.png`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "svg" => Some(SnapshotFormat::Svg),
+            "ansi" => Some(SnapshotFormat::Ansi),
+            "txt" | "text" => Some(SnapshotFormat::Text),
+            "png" => Some(SnapshotFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Renders the buffer in `format`; only PNG can fail, e.g. when built
+/// without the `png` feature.
+pub fn render(buffer: &Buffer, format: SnapshotFormat) -> io::Result<Vec<u8>> {
+    match format {
+        SnapshotFormat::Svg => Ok(svg(buffer).into_bytes()),
+        SnapshotFormat::Ansi => Ok(ansi(buffer).into_bytes()),
+        SnapshotFormat::Text => Ok(text(buffer).into_bytes()),
+        SnapshotFormat::Png => png(buffer),
+    }
+}
+
+/// Writes the buffer to `path` in the format given by its extension.
+pub fn write(buffer: &Buffer, path: &Path) -> io::Result<()> {
+    let format = SnapshotFormat::from_path(path).ok_or_else(|| io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("unsupported snapshot format {} (expected .svg, .ansi, .txt or .png)", path.display()),
+    ))?;
+
+    fs::write(path, render(buffer, format)?)
+}
+
+/// Rasterises the SVG output with the system's fonts, one SVG pixel per pixel.
+#[cfg(feature = "png")]
+pub fn png(buffer: &Buffer) -> io::Result<Vec<u8>> {
+    use resvg::{
+        tiny_skia::{Pixmap, Transform},
+        usvg::{Options, Tree},
+    };
+
+    let mut options = Options::default();
+    options.fontdb_mut().load_system_fonts();
+
+    let tree = Tree::from_str(&svg(buffer), &options).map_err(io::Error::other)?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| io::Error::other("snapshot has no area"))?;
+
+    resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(io::Error::other)
+}
+
+#[cfg(not(feature = "png"))]
+pub fn png(_: &Buffer) -> io::Result<Vec<u8>> {
+    Err(io::Error::other("built without png support"))
+}
+
+fn rows(buffer: &Buffer) -> Vec<&[Cell]> {
+    buffer.content.chunks(buffer.area.width.max(1) as usize).collect()
+}
+
+pub fn text(buffer: &Buffer) -> String {
+    rows(buffer)
+        .iter()
+        .map(|row| row.iter().map(|c| c.symbol.as_str()).collect::<String>().trim_end().to_string() + "\n")
+        .collect()
+}
+
+pub fn ansi(buffer: &Buffer) -> String {
+    let mut out = String::new();
+
+    for row in rows(buffer) {
+        let mut style = None;
+
+        for cell in row.iter() {
+            let current = (cell.fg, cell.bg, cell.modifier);
+
+            if style != Some(current) {
+                out += "\x1b[0";
+
+                if let Some(code) = sgr(cell.fg, false) {
+                    out += &format!(";{}", code);
+                }
+
+                if let Some(code) = sgr(cell.bg, true) {
+                    out += &format!(";{}", code);
+                }
+
+                if cell.modifier.contains(Modifier::BOLD) {
+                    out += ";1";
+                }
+
+                out += "m";
+                style = Some(current);
+            }
+
+            out += &cell.symbol;
+        }
+
+        out += "\x1b[0m\n";
+    }
+
+    out
+}
+
+/// SGR parameters selecting `color`, `None` for the terminal default.
+fn sgr(color: Color, background: bool) -> Option<String> {
+    let offset = match background {
+        true => 10,
+        false => 0,
+    };
+
+    let base = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", 38 + offset, r, g, b)),
+        Color::Indexed(n) => return Some(format!("{};5;{}", 38 + offset, n)),
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+    };
+
+    Some((base + offset).to_string())
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Draws every cell as SVG text, runs of equally styled cells sharing one
+/// element stretched to the width of the cells.
+pub fn svg(buffer: &Buffer) -> String {
+    let width = buffer.area.width as f64 * CELL_WIDTH;
+    let height = buffer.area.height as f64 * CELL_HEIGHT;
+
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.1}" height="{h:.1}" viewBox="0 0 {w:.1} {h:.1}">"#,
+        w = width,
+        h = height,
+    );
+    let _ = writeln!(out, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex(DEFAULT_BG));
+    let _ = writeln!(
+        out,
+        r#"<g font-family="DejaVu Sans Mono, Menlo, Consolas, monospace" font-size="{}" xml:space="preserve">"#,
+        FONT_SIZE,
+    );
+
+    for (y, row) in rows(buffer).iter().enumerate() {
+        let mut start = 0;
+
+        while start < row.len() {
+            let first = &row[start];
+            let end = start + row[start..]
+                .iter()
+                .take_while(|c| c.fg == first.fg && c.bg == first.bg && c.modifier == first.modifier)
+                .count();
+
+            let x = start as f64 * CELL_WIDTH;
+            let run_width = (end - start) as f64 * CELL_WIDTH;
+
+            if first.bg != Color::Reset {
+                let _ = writeln!(
+                    out,
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+                    x, y as f64 * CELL_HEIGHT, run_width, CELL_HEIGHT, hex(rgb(first.bg, DEFAULT_BG)),
+                );
+            }
+
+            let content: String = row[start..end].iter().map(|c| c.symbol.as_str()).collect();
+
+            if !content.trim().is_empty() {
+                let weight = match first.modifier.contains(Modifier::BOLD) {
+                    true => r#" font-weight="bold""#,
+                    false => "",
+                };
+
+                let _ = writeln!(
+                    out,
+                    r#"<text x="{:.1}" y="{:.1}" fill="{}" textLength="{:.1}" lengthAdjust="spacingAndGlyphs"{}>{}</text>"#,
+                    x,
+                    (y as f64 + 0.8) * CELL_HEIGHT,
+                    hex(rgb(first.fg, DEFAULT_FG)),
+                    run_width,
+                    weight,
+                    escape(&content),
+                );
+            }
+
+            start = end;
+        }
+    }
+
+    out += "</g>\n</svg>\n";
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::{layout::Rect, style::Style};
+
+    /// `a<b` in red on the first row, bold `&` on blue on the second.
+    fn buffer() -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 5, 2));
+        buffer.set_string(0, 0, "a<b", Style::default().fg(Color::Red));
+        buffer.set_string(1, 1, "&", Style::default().bg(Color::Blue).add_modifier(Modifier::BOLD));
+        buffer
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(SnapshotFormat::from_path(Path::new("a.SVG")), Some(SnapshotFormat::Svg));
+        assert_eq!(SnapshotFormat::from_path(Path::new("a.ansi")), Some(SnapshotFormat::Ansi));
+        assert_eq!(SnapshotFormat::from_path(Path::new("dir/a.text")), Some(SnapshotFormat::Text));
+        assert_eq!(SnapshotFormat::from_path(Path::new("a.png")), Some(SnapshotFormat::Png));
+        assert_eq!(SnapshotFormat::from_path(Path::new("a.gif")), None);
+        assert_eq!(SnapshotFormat::from_path(Path::new("svg")), None);
+    }
+
+    #[test]
+    fn text_trims_rows() {
+        assert_eq!(text(&buffer()), "a<b\n &\n");
+    }
+
+    #[test]
+    fn ansi_changes_style_between_runs() {
+        assert_eq!(
+            ansi(&buffer()),
+            "\x1b[0;31ma<b\x1b[0m  \x1b[0m\n\x1b[0m \x1b[0;44;1m&\x1b[0m   \x1b[0m\n",
+        );
+    }
+
+    #[test]
+    fn sgr_resolves_colors() {
+        assert_eq!(sgr(Color::Reset, false), None);
+        assert_eq!(sgr(Color::LightGreen, true), Some("102".into()));
+        assert_eq!(sgr(Color::Indexed(208), false), Some("38;5;208".into()));
+    }
+
+    #[test]
+    fn svg_draws_runs_of_cells() {
+        let svg = svg(&buffer());
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="42.0" height="34.0""#));
+        assert!(svg.contains(r##"fill="#cd0000" textLength="25.2" lengthAdjust="spacingAndGlyphs">a&lt;b</text>"##));
+        assert!(svg.contains(r##"<rect x="8.4" y="17.0" width="8.4" height="17.0" fill="#0000ee"/>"##));
+        assert!(svg.contains(r#" font-weight="bold">&amp;</text>"#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn render_matches_the_format() {
+        let buffer = buffer();
+
+        assert_eq!(render(&buffer, SnapshotFormat::Text).unwrap(), text(&buffer).into_bytes());
+        assert_eq!(render(&buffer, SnapshotFormat::Svg).unwrap(), svg(&buffer).into_bytes());
+        assert_eq!(render(&buffer, SnapshotFormat::Png).is_ok(), cfg!(feature = "png"));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_matches_svg_size() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 40, 6));
+        buffer.set_string(0, 0, "AAPL 150.25 ⣀⡠⠔⠊", Style::default().fg(Color::Green));
+
+        let png = png(&buffer).unwrap();
+        let dimension = |at: usize| u32::from_be_bytes([png[at], png[at + 1], png[at + 2], png[at + 3]]);
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(dimension(16), (40.0 * CELL_WIDTH).ceil() as u32);
+        assert_eq!(dimension(20), (6.0 * CELL_HEIGHT).ceil() as u32);
+    }
+}
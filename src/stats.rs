@@ -0,0 +1,171 @@
+use crate::ticker::Data;
+use chrono::NaiveDate;
+
+/// Trading days per year, used to annualize the volatility of daily bars.
+pub const TRADING_DAYS: f64 = 252.0;
+
+/// Summary statistics of the bars of a chart.
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    pub open: f64,
+    pub close: f64,
+    pub high: (f64, NaiveDate),
+    pub low: (f64, NaiveDate),
+    /// Mean and median of the closes.
+    pub mean: f64,
+    pub median: f64,
+    /// Standard deviation of the daily returns, as a fraction.
+    pub stddev: Option<f64>,
+    pub volatility: Option<f64>,
+    /// Largest fall from a peak close, as a (negative) fraction, with the
+    /// dates of the peak and the trough.
+    pub max_drawdown: Option<(f64, NaiveDate, NaiveDate)>,
+    pub average_volume: f64,
+    pub up_days: usize,
+    pub down_days: usize,
+}
+
+impl Statistics {
+    pub fn compute(data: &Data) -> Option<Self> {
+        if data.is_empty() {
+            return None;
+        }
+
+        let closes: Vec<f64> = data.price_data().iter().map(|p| f64::from(*p)).collect();
+        let dates = data.dates();
+        let date = |idx: usize| dates.get(idx).copied().unwrap_or_default();
+
+        let extreme = |values: &[f64], better: fn(f64, f64) -> bool| {
+            let mut idx = 0;
+
+            for (i, v) in values.iter().enumerate() {
+                if better(*v, values[idx]) {
+                    idx = i;
+                }
+            }
+
+            (values[idx], date(idx))
+        };
+
+        let high = extreme(data.high_data(), |a, b| a > b);
+        let low = extreme(data.low_data(), |a, b| a < b);
+
+        let mean = closes.iter().sum::<f64>() / closes.len() as f64;
+
+        let mut sorted = closes.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let median = match sorted.len() % 2 {
+            0 => (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0,
+            _ => sorted[sorted.len() / 2],
+        };
+
+        let returns: Vec<f64> = closes
+            .windows(2)
+            .filter(|w| w[0] != 0.0)
+            .map(|w| w[1] / w[0] - 1.0)
+            .collect();
+
+        let stddev = match returns.len() > 1 {
+            true => {
+                let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+                let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+                Some(variance.sqrt())
+            }
+            false => None,
+        };
+
+        let mut max_drawdown = None;
+        let mut peak = 0;
+
+        for (idx, close) in closes.iter().enumerate() {
+            if *close > closes[peak] {
+                peak = idx;
+            }
+
+            if closes[peak] > 0.0 {
+                let drawdown = close / closes[peak] - 1.0;
+
+                if drawdown < max_drawdown.map(|(d, _, _)| d).unwrap_or(0.0) {
+                    max_drawdown = Some((drawdown, date(peak), date(idx)));
+                }
+            }
+        }
+
+        let volumes = data.volume_data();
+
+        Some(Self {
+            open: data.open_data().first().copied().unwrap_or(closes[0]),
+            close: closes[closes.len() - 1],
+            high,
+            low,
+            mean,
+            median,
+            stddev,
+            volatility: stddev.map(|s| s * TRADING_DAYS.sqrt()),
+            max_drawdown,
+            average_volume: volumes.iter().sum::<u64>() as f64 / volumes.len().max(1) as f64,
+            up_days: closes.windows(2).filter(|w| w[1] > w[0]).count(),
+            down_days: closes.windows(2).filter(|w| w[1] < w[0]).count(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ordered_float::OrderedFloat;
+
+    /// Daily closes from Jan 1 1970, with volumes of 100, 200, ...
+    fn data(closes: &[f64]) -> Data {
+        Data::new(
+            closes.iter().map(|c| OrderedFloat::from(*c)).collect(),
+            closes.iter().map(|_| String::new()).collect(),
+            (1..=closes.len() as u64).map(|n| n * 100).collect(),
+            (0..closes.len() as i64).map(|day| day * 86_400).collect(),
+        )
+    }
+
+    fn day(n: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(1970, 1, n).unwrap()
+    }
+
+    #[test]
+    fn compute_needs_bars() {
+        assert!(Statistics::compute(&Data::empty()).is_none());
+    }
+
+    #[test]
+    fn compute_a_single_bar() {
+        let stats = Statistics::compute(&data(&[10.0])).unwrap();
+
+        assert_eq!((stats.open, stats.close), (10.0, 10.0));
+        assert_eq!(stats.high, (10.0, day(1)));
+        assert_eq!(stats.low, (10.0, day(1)));
+        assert_eq!((stats.mean, stats.median), (10.0, 10.0));
+        assert_eq!(stats.stddev, None);
+        assert_eq!(stats.volatility, None);
+        assert_eq!(stats.max_drawdown, None);
+        assert_eq!((stats.up_days, stats.down_days), (0, 0));
+    }
+
+    #[test]
+    fn compute_a_known_series() {
+        let stats = Statistics::compute(&data(&[10.0, 12.0, 9.0, 11.0, 8.0, 13.0])).unwrap();
+
+        assert_eq!((stats.open, stats.close), (10.0, 13.0));
+        assert_eq!(stats.high, (13.0, day(6)));
+        assert_eq!(stats.low, (8.0, day(5)));
+        assert_eq!((stats.mean, stats.median), (10.5, 10.5));
+        assert_eq!(stats.average_volume, 350.0);
+        assert_eq!((stats.up_days, stats.down_days), (3, 2));
+
+        let (drawdown, peak, trough) = stats.max_drawdown.unwrap();
+        assert!((drawdown + 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!((peak, trough), (day(2), day(5)));
+
+        let stddev = stats.stddev.unwrap();
+        assert!(stddev > 0.0);
+        assert!((stats.volatility.unwrap() - stddev * TRADING_DAYS.sqrt()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{message::Message, ticker::Ticker};
+use std::collections::HashMap;
+
+/// A symbol as stored and looked up. Quotes and responses do not always echo
+/// the casing a symbol was requested with.
+pub fn normalize(symbol: &str) -> String {
+    symbol.trim().to_uppercase()
+}
+
+/// The tickers shown by the UI, in display order and keyed by normalized
+/// symbol. The revision changes with every update, so the UI only redraws
+/// when something did.
+#[derive(Debug, Default)]
+pub struct Store {
+    tickers: Vec<Ticker>,
+    index: HashMap<String, usize>,
+    revision: u64,
+}
+
+impl Store {
+    /// Keeps the order of `tickers`, dropping repeated symbols.
+    pub fn new(tickers: Vec<Ticker>) -> Self {
+        let mut store = Self::default();
+
+        for ticker in tickers {
+            store.insert(ticker);
+        }
+
+        store
+    }
+
+    /// Appends `ticker` unless its symbol is already stored, returning whether
+    /// it was added.
+    pub fn insert(&mut self, ticker: Ticker) -> bool {
+        let key = normalize(ticker.identifier());
+
+        match self.index.contains_key(&key) {
+            true => false,
+            false => {
+                self.index.insert(key, self.tickers.len());
+                self.tickers.push(ticker);
+                self.touch();
+                true
+            }
+        }
+    }
+
+    pub fn tickers(&self) -> &Vec<Ticker> {
+        &self.tickers
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Display position of `symbol`.
+    pub fn position(&self, symbol: &str) -> Option<usize> {
+        self.index.get(&normalize(symbol)).copied()
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.position(symbol).is_some()
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Ticker> {
+        self.position(symbol).map(|idx| &self.tickers[idx])
+    }
+
+    /// The ticker of `symbol` for updating, marking the store changed.
+    pub fn get_mut(&mut self, symbol: &str) -> Option<&mut Ticker> {
+        let idx = self.position(symbol)?;
+
+        self.touch();
+        self.tickers.get_mut(idx)
+    }
+
+    /// Applies a provider response or quote to its ticker, returning the
+    /// ticker. Messages for symbols that are not stored, or that are not about
+    /// a single ticker, are ignored.
+    pub fn apply(&mut self, message: Message) -> Option<&Ticker> {
+        use Message::*;
+
+        let ticker = match message {
+            YearRange((symbol, range)) => {
+                let ticker = self.get_mut(&symbol)?;
+                ticker.set_year_range(range);
+                ticker
+            }
+            MarketCap((symbol, cap)) => {
+                let ticker = self.get_mut(&symbol)?;
+                ticker.set_market_cap(cap);
+                ticker
+            }
+            ProfileInit((symbol, profile)) => {
+                let ticker = self.get_mut(&symbol)?;
+                ticker.init_info(profile);
+                ticker
+            }
+            // responses to superseded requests are dropped by the ticker
+            DataUpdate((symbol, generation, data)) => {
+                let ticker = self.get_mut(&symbol)?;
+                ticker.receive(generation, data);
+                ticker
+            }
+            PriceUpdate((symbol, price, _)) => {
+                let ticker = self.get_mut(&symbol)?;
+                ticker.set_realtime_price(price);
+                ticker
+            }
+            ActionFailed(_) | Start => return None,
+        };
+
+        Some(ticker)
+    }
+
+    /// Marks the store changed, e.g. after state drawn next to it was updated.
+    pub fn touch(&mut self) {
+        self.revision += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticker::Data;
+
+    fn store(symbols: &[&str]) -> Store {
+        Store::new(symbols.iter().map(|s| Ticker::new(s.to_string())).collect())
+    }
+
+    fn symbols(store: &Store) -> Vec<&str> {
+        store.tickers().iter().map(|t| t.identifier().as_str()).collect()
+    }
+
+    #[test]
+    fn normalize_trims_and_uppercases() {
+        assert_eq!(normalize(" aapl\t"), "AAPL");
+        assert_eq!(normalize("brk.b"), "BRK.B");
+        assert_eq!(normalize("MSFT"), "MSFT");
+    }
+
+    #[test]
+    fn new_keeps_order_and_drops_duplicates() {
+        let store = store(&["MSFT", "aapl", "SPY", "AAPL", " msft "]);
+
+        assert_eq!(symbols(&store), vec!["MSFT", "aapl", "SPY"]);
+        assert_eq!(store.position("SPY"), Some(2));
+    }
+
+    #[test]
+    fn lookups_ignore_case() {
+        let mut store = store(&["AAPL", "msft"]);
+
+        assert_eq!(store.position("aapl"), Some(0));
+        assert_eq!(store.position("MSFT"), Some(1));
+        assert_eq!(store.get("Msft").map(|t| t.identifier().as_str()), Some("msft"));
+        assert!(store.contains(" aapl "));
+
+        let revision = store.revision();
+        assert_eq!(store.get_mut("aApL").map(|t| t.identifier().clone()), Some("AAPL".to_string()));
+        assert!(store.revision() > revision);
+    }
+
+    #[test]
+    fn get_mut_unknown_symbol_is_none() {
+        let mut store = store(&["AAPL"]);
+        let revision = store.revision();
+
+        assert!(store.get_mut("TSLA").is_none());
+        assert!(store.get("TSLA").is_none());
+        assert_eq!(store.revision(), revision);
+    }
+
+    #[test]
+    fn apply_ignores_unknown_symbols() {
+        let mut store = store(&["AAPL"]);
+        let revision = store.revision();
+
+        assert!(store.apply(Message::PriceUpdate(("TSLA".into(), 250.0, 1000))).is_none());
+        assert!(store.apply(Message::DataUpdate(("TSLA".into(), 1, Ok(Data::empty())))).is_none());
+
+        assert_eq!(symbols(&store), vec!["AAPL"]);
+        assert_eq!(store.revision(), revision);
+        assert_eq!(store.get("AAPL").map(|t| t.realtime_price()), Some(0.0));
+    }
+
+    #[test]
+    fn apply_updates_known_symbol_in_other_case() {
+        let mut store = store(&["AAPL"]);
+
+        let price = store.apply(Message::PriceUpdate(("aapl".into(), 150.0, 1000))).map(|t| t.realtime_price());
+        assert_eq!(price, Some(150.0));
+    }
+}
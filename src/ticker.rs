@@ -1,19 +1,34 @@
+use crate::{provider::Provider, range::DateRange, utils::timestamp_to_date};
+use chrono::NaiveDate;
+use std::ops::Range;
 use ordered_float::OrderedFloat;
-use yahoo_finance::{Profile, history, Interval, Timestamped};
+use yahoo_finance::{Bar, Interval, Profile, Timestamped};
 #[derive(Clone, Debug)]
 pub struct Info {
     name: String,
+    sector: Option<String>,
+    industry: Option<String>,
+    asset_type: Option<String>,
 }
 
 impl From<Profile> for Info {
     fn from(p: Profile) -> Self {
-        let name = match p {
-            Profile::Company(p) => p.name,
-            Profile::Fund(p) => p.name,
-        };
-
-        Self {
-            name
+        match p {
+            Profile::Company(p) => Self {
+                name: p.name,
+                sector: p.sector,
+                industry: p.industry,
+                asset_type: Some("Stock".into()),
+            },
+            Profile::Fund(p) => Self {
+                name: p.name,
+                sector: None,
+                industry: None,
+                asset_type: Some(match p.kind.is_empty() {
+                    true => "Fund".into(),
+                    false => p.kind,
+                }),
+            },
         }
     }
 }
@@ -22,12 +37,28 @@ impl Info {
     pub fn unknown() -> Self {
         Self {
             name: String::new(),
+            sector: None,
+            industry: None,
+            asset_type: None,
         }
     }
 
     pub fn name(&self) -> &String {
         &self.name
     }
+
+    pub fn sector(&self) -> Option<&str> {
+        self.sector.as_deref()
+    }
+
+    pub fn industry(&self) -> Option<&str> {
+        self.industry.as_deref()
+    }
+
+    /// `Stock` for companies, the fund kind (e.g. `ETF`) for funds.
+    pub fn asset_type(&self) -> Option<&str> {
+        self.asset_type.as_deref()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -35,6 +66,11 @@ pub struct Data {
     price_data: Vec<OrderedFloat<f64>>,
     date_data: Vec<String>,
     volume_data: Vec<u64>,
+    /// Unix timestamps (seconds) of each bar.
+    timestamps: Vec<i64>,
+    open_data: Vec<f64>,
+    high_data: Vec<f64>,
+    low_data: Vec<f64>,
 }
 
 impl Data {
@@ -43,17 +79,73 @@ impl Data {
             price_data: vec![],
             date_data: vec![],
             volume_data: vec![],
+            timestamps: vec![],
+            open_data: vec![],
+            high_data: vec![],
+            low_data: vec![],
         }
     }
 
-    pub fn new(price_data: Vec<OrderedFloat<f64>>, date_data: Vec<String>, volume_data: Vec<u64>) -> Self {
+    /// Builds close-only data; open, high and low are taken to be the close.
+    pub fn new(price_data: Vec<OrderedFloat<f64>>, date_data: Vec<String>, volume_data: Vec<u64>, timestamps: Vec<i64>) -> Self {
+        let closes: Vec<f64> = price_data.iter().map(|p| f64::from(*p)).collect();
+
         Self {
             price_data,
             date_data,
             volume_data,
+            timestamps,
+            open_data: closes.clone(),
+            high_data: closes.clone(),
+            low_data: closes,
         }
     }
 
+    pub fn from_bars(bars: &[Bar]) -> Self {
+        Self {
+            price_data: bars.iter().map(|b| OrderedFloat::from(b.close)).collect(),
+            date_data: bars.iter().map(|b| b.datetime().format("%b %e %Y").to_string()).collect(),
+            volume_data: bars.iter().map(|b| b.volume.unwrap_or(0)).collect(),
+            timestamps: bars.iter().map(|b| b.timestamp_seconds()).collect(),
+            open_data: bars.iter().map(|b| b.open).collect(),
+            high_data: bars.iter().map(|b| b.high).collect(),
+            low_data: bars.iter().map(|b| b.low).collect(),
+        }
+    }
+
+    pub fn open_data(&self) -> &Vec<f64> {
+        &self.open_data
+    }
+
+    pub fn high_data(&self) -> &Vec<f64> {
+        &self.high_data
+    }
+
+    pub fn low_data(&self) -> &Vec<f64> {
+        &self.low_data
+    }
+
+    pub fn len(&self) -> usize {
+        self.price_data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.price_data.is_empty()
+    }
+
+    pub fn timestamps(&self) -> &Vec<i64> {
+        &self.timestamps
+    }
+
+    /// Trading dates of each bar, in UTC.
+    pub fn dates(&self) -> Vec<NaiveDate> {
+        self.timestamps.iter().map(|ts| timestamp_to_date(*ts)).collect()
+    }
+
+    pub fn date_data(&self) -> &Vec<String> {
+        &self.date_data
+    }
+
     pub fn price_data_mut(&mut self) -> &mut Vec<OrderedFloat<f64>> {
         &mut self.price_data
     }
@@ -69,6 +161,24 @@ impl Data {
     pub fn set_price_data(&mut self, data: Vec<OrderedFloat<f64>>) {
         self.price_data = data;
     }
+
+    /// The bars in `range`, clamped to the loaded ones.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        fn part<T: Clone>(values: &[T], range: &Range<usize>) -> Vec<T> {
+            let end = range.end.min(values.len());
+            values[range.start.min(end)..end].to_vec()
+        }
+
+        Self {
+            price_data: part(&self.price_data, &range),
+            date_data: part(&self.date_data, &range),
+            volume_data: part(&self.volume_data, &range),
+            timestamps: part(&self.timestamps, &range),
+            open_data: part(&self.open_data, &range),
+            high_data: part(&self.high_data, &range),
+            low_data: part(&self.low_data, &range),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -76,8 +186,19 @@ pub struct Ticker {
     data: Data,
     info: Info,
     interval: Interval,
+    /// Custom period shown instead of the interval.
+    range: Option<DateRange>,
     identifier: String,
     realtime_price: f64,
+    /// Lowest low and highest high of the last 52 weeks.
+    year_range: Option<(f64, f64)>,
+    /// Market capitalisation in the quote currency.
+    market_cap: Option<f64>,
+    /// Generation of the history request still awaited, with the interval
+    /// and range it loads.
+    pending: Option<(u64, Interval, Option<DateRange>)>,
+    /// Why the latest history request failed.
+    error: Option<String>,
 }
 
 impl Ticker {
@@ -85,9 +206,14 @@ impl Ticker {
         Self {
             identifier,
             interval: Interval::_6mo,
+            range: None,
             realtime_price: 0.0,
             info: Info::unknown(),
             data: Data::empty(),
+            year_range: None,
+            market_cap: None,
+            pending: None,
+            error: None,
         }
     }
 
@@ -99,6 +225,18 @@ impl Ticker {
         &self.interval
     }
 
+    pub fn range(&self) -> Option<DateRange> {
+        self.range
+    }
+
+    /// The charted period, for titles: the custom range or the interval.
+    pub fn period(&self) -> String {
+        match self.range {
+            Some(range) => range.to_string(),
+            None => self.interval.to_string(),
+        }
+    }
+
     pub fn set_realtime_price(&mut self, val: f64) {
         self.realtime_price = val;
     }
@@ -127,12 +265,109 @@ impl Ticker {
         }
     }
 
+    /// Close of the bar before the latest one, used as the reference for the day's change.
+    pub fn previous_close(&self) -> Option<f64> {
+        let prices = self.data.price_data();
+        prices.len().checked_sub(2).map(|idx| f64::from(prices[idx]))
+    }
+
+    pub fn year_range(&self) -> Option<(f64, f64)> {
+        self.year_range
+    }
+
+    pub fn set_year_range(&mut self, range: (f64, f64)) {
+        self.year_range = Some(range);
+    }
+
+    pub fn market_cap(&self) -> Option<f64> {
+        self.market_cap
+    }
+
+    pub fn set_market_cap(&mut self, cap: f64) {
+        self.market_cap = Some(cap);
+    }
+
+    /// Where the current price sits within the 52-week range, from 0 at the
+    /// low to 1 at the high.
+    pub fn range_position(&self) -> Option<f64> {
+        let (low, high) = self.year_range.filter(|(low, high)| high > low)?;
+        Some(((self.realtime_price() - low) / (high - low)).clamp(0.0, 1.0))
+    }
+
+    /// A copy of the ticker with only the bars in `range`; the rest of the
+    /// history is not cloned.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        Self {
+            data: self.data.slice(range),
+            info: self.info.clone(),
+            interval: self.interval,
+            range: self.range,
+            identifier: self.identifier.clone(),
+            realtime_price: self.realtime_price,
+            year_range: self.year_range,
+            market_cap: self.market_cap,
+            pending: self.pending,
+            error: self.error.clone(),
+        }
+    }
+
     pub fn set_data(&mut self, data: Data) {
         self.data = data;
     }
 
+    /// Whether the history of the latest request is still awaited.
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Why the latest history request failed, while no other is pending.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// The period being loaded, or the charted one while nothing is pending.
+    pub fn requested_period(&self) -> (Interval, Option<DateRange>) {
+        match self.pending {
+            Some((_, interval, range)) => (interval, range),
+            None => (self.interval, self.range),
+        }
+    }
+
+    /// Awaits the history of request `generation` over `interval`, or `range`
+    /// if any, superseding earlier ones. The charted period only switches once
+    /// that history arrives.
+    pub fn request(&mut self, generation: u64, interval: Interval, range: Option<DateRange>) {
+        self.pending = Some((generation, interval, range));
+        self.error = None;
+    }
+
+    /// Takes the response to request `generation` unless a later request
+    /// superseded it, returning whether it was taken. On failure the previous
+    /// history and period are kept.
+    pub fn receive(&mut self, generation: u64, data: Result<Data, String>) -> bool {
+        let (interval, range) = match self.pending {
+            Some((pending, interval, range)) if pending == generation => (interval, range),
+            _ => return false,
+        };
+
+        self.pending = None;
+
+        match data {
+            Ok(data) => {
+                self.data = data;
+                self.interval = interval;
+                self.range = range;
+            }
+            Err(e) => self.error = Some(e),
+        }
+
+        true
+    }
+
+    /// Switches to a preset interval, dropping any custom range.
     pub fn set_interval(&mut self, interval: Interval) {
         self.interval = interval;
+        self.range = None;
     }
 
     pub async fn get_profile(&mut self) {
@@ -145,20 +380,7 @@ impl Ticker {
     }
 
     pub async fn get_data(&mut self) -> Result<(), yahoo_finance::Error> {
-        let hist = history::retrieve_interval(&self.identifier, self.interval).await?;
-
-        let mut price_data = vec![];
-        let mut date_data = vec![];
-        let mut volume_data = vec![];
-
-        for d in hist.iter() {
-            price_data.push(OrderedFloat::from(d.close));
-            date_data.push(format!("{}", d.datetime().format("%b %e %Y")));
-            volume_data.push(d.volume.unwrap());
-        }
-
-        self.data = Data::new(price_data, date_data, volume_data);
-        
+        self.data = Provider::Yahoo.history(&self.identifier, self.interval).await?;
         Ok(())
     }
 
@@ -178,3 +400,81 @@ impl Ticker {
         &self.data.date_data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(prices: &[f64]) -> Data {
+        Data::new(
+            prices.iter().map(|p| OrderedFloat::from(*p)).collect(),
+            prices.iter().map(|_| String::new()).collect(),
+            prices.iter().map(|_| 0).collect(),
+            (0..prices.len() as i64).collect(),
+        )
+    }
+
+    #[test]
+    fn receive_commits_the_requested_period() {
+        let mut ticker = Ticker::new("AAPL".into());
+        ticker.request(1, Interval::_1y, None);
+
+        assert!(ticker.is_loading());
+        assert_eq!(ticker.period(), Interval::_6mo.to_string());
+        assert_eq!(ticker.requested_period().0.to_string(), Interval::_1y.to_string());
+
+        assert!(ticker.receive(1, Ok(data(&[1.0, 2.0]))));
+        assert!(!ticker.is_loading());
+        assert_eq!(ticker.period(), Interval::_1y.to_string());
+        assert_eq!(ticker.data().len(), 2);
+    }
+
+    #[test]
+    fn receive_ignores_a_stale_generation() {
+        let mut ticker = Ticker::new("AAPL".into());
+        ticker.request(1, Interval::_1y, None);
+        ticker.request(2, Interval::_2y, None);
+
+        assert!(!ticker.receive(1, Ok(data(&[1.0]))));
+        assert!(ticker.is_loading());
+        assert!(ticker.data().is_empty());
+        assert_eq!(ticker.period(), Interval::_6mo.to_string());
+
+        assert!(ticker.receive(2, Ok(data(&[1.0, 2.0]))));
+        assert_eq!(ticker.period(), Interval::_2y.to_string());
+        assert!(!ticker.receive(2, Ok(data(&[1.0]))));
+    }
+
+    #[test]
+    fn receive_keeps_the_period_on_error() {
+        let mut ticker = Ticker::new("AAPL".into());
+        ticker.request(1, Interval::_1y, None);
+        ticker.receive(1, Ok(data(&[1.0, 2.0])));
+
+        let range = DateRange::new(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(), None).unwrap();
+        ticker.request(2, Interval::_1y, Some(range));
+        assert!(ticker.receive(2, Err("timed out".into())));
+
+        assert_eq!(ticker.error(), Some("timed out"));
+        assert_eq!(ticker.range(), None);
+        assert_eq!(ticker.period(), Interval::_1y.to_string());
+        assert_eq!(ticker.data().len(), 2);
+
+        ticker.request(3, Interval::_5d, None);
+        assert_eq!(ticker.error(), None);
+    }
+
+    #[test]
+    fn slice_keeps_the_bars_in_range() {
+        let mut ticker = Ticker::new("AAPL".into());
+        ticker.set_data(data(&[1.0, 2.0, 3.0, 4.0]));
+        ticker.set_year_range((1.0, 4.0));
+
+        let slice = ticker.slice(1..3);
+
+        assert_eq!(slice.price_data(), vec![OrderedFloat(2.0), OrderedFloat(3.0)]);
+        assert_eq!(slice.identifier(), "AAPL");
+        assert_eq!(slice.year_range(), Some((1.0, 4.0)));
+        assert_eq!(ticker.slice(3..10).data().len(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    alert::{Alerts, Notification},
+    ui::theme::Theme,
+};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+pub fn render<B: Backend>(f: &mut Frame<B>, area: Rect, alerts: &Alerts, selected: usize, theme: &Theme) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let mut rules: Vec<ListItem> = alerts.rules().iter().enumerate().map(|(idx, rule)| {
+        let style = match (idx == selected, rule.is_triggered()) {
+            (true, _) => Style::default().fg(theme.selected).add_modifier(Modifier::BOLD),
+            (false, true) => Style::default().fg(theme.alert),
+            (false, false) => Style::default(),
+        };
+
+        let state = match rule.is_triggered() {
+            true => "triggered",
+            false => "armed",
+        };
+
+        ListItem::new(Spans::from(vec![
+            Span::styled(format!("{:<40}", rule.to_string()), style),
+            Span::styled(state, Style::default().fg(theme.label)),
+        ]))
+    }).collect();
+
+    if rules.is_empty() {
+        rules.push(ListItem::new(Span::styled(
+            "No alert rules, press n to add one or add [[alerts]] to the configuration",
+            Style::default().fg(theme.label),
+        )));
+    }
+
+    let mut history: Vec<ListItem> = alerts.history().iter().rev().map(|n| {
+        ListItem::new(Spans::from(vec![
+            Span::styled(n.time.format("%Y-%m-%d %H:%M:%S  ").to_string(), Style::default().fg(theme.label)),
+            Span::raw(n.message.clone()),
+        ]))
+    }).collect();
+
+    if history.is_empty() {
+        history.push(ListItem::new(Span::styled("No alerts fired yet", Style::default().fg(theme.label))));
+    }
+
+    if let Some(error) = alerts.errors().last() {
+        history.insert(0, ListItem::new(Span::styled(error.clone(), Style::default().fg(theme.down))));
+    }
+
+    let block = |title: &'static str| {
+        Block::default()
+            .title(Span::styled(title, Style::default().fg(theme.title).add_modifier(Modifier::BOLD)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+    };
+
+    let rules = List::new(rules)
+        .block(block("Alert rules (n: new, d: delete, j/k: select)"))
+        .style(Style::default().fg(theme.text));
+
+    let history = List::new(history)
+        .block(block("History"))
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(rules, chunks[0]);
+    f.render_widget(history, chunks[1]);
+}
+
+/// Draws the fired alert in a box centered over `area`.
+pub fn popup<B: Backend>(f: &mut Frame<B>, area: Rect, notification: &Notification, theme: &Theme) {
+    let width = (notification.message.len() as u16 + 4).max(40).min(area.width);
+    let height = 5.min(area.height);
+
+    let rect = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let text = vec![
+        Spans::from(Span::styled(notification.message.clone(), Style::default().fg(theme.alert).add_modifier(Modifier::BOLD))),
+        Spans::from(Span::styled("Enter/Esc to dismiss", Style::default().fg(theme.label))),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default()
+            .title(Span::styled("Alert", Style::default().fg(theme.alert).add_modifier(Modifier::BOLD)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.alert))
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, rect);
+    f.render_widget(paragraph, rect);
+}
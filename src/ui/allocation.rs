@@ -0,0 +1,74 @@
+use crate::{
+    allocation::{Allocation, Grouping},
+    ui::theme::Theme,
+};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Drift, in percentage points, beyond which a slice is flagged for rebalancing.
+const DRIFT_BAND: f64 = 5.0;
+
+const NAME_WIDTH: usize = 24;
+
+pub fn render<B: Backend>(f: &mut Frame<B>, area: Rect, allocation: &Allocation, grouping: Grouping, theme: &Theme) {
+    // name, bar, weight and the target/drift/trade columns share the inner width
+    let bar_width = (area.width as usize).saturating_sub(NAME_WIDTH + 58).max(10);
+
+    let mut lines: Vec<Spans> = allocation.slices.iter().map(|slice| {
+        let filled = ((slice.weight / 100.0) * bar_width as f64).round() as usize;
+        let filled = filled.min(bar_width);
+
+        let mut spans = vec![
+            Span::styled(
+                format!("{:<width$.width$} ", slice.name, width = NAME_WIDTH),
+                Style::default().fg(theme.selected),
+            ),
+            Span::styled("█".repeat(filled), Style::default().fg(theme.price)),
+            Span::styled("░".repeat(bar_width - filled), Style::default().fg(theme.border)),
+            Span::raw(format!(" {:>6.2}%", slice.weight)),
+        ];
+
+        if let (Some(target), Some(drift), Some(trade)) = (slice.target, slice.drift(), slice.trade(allocation.total)) {
+            let style = match drift.abs() > DRIFT_BAND {
+                true => Style::default().fg(theme.down).add_modifier(Modifier::BOLD),
+                false => Style::default().fg(theme.text),
+            };
+
+            let action = match trade < 0.0 {
+                true => "sell",
+                false => "buy",
+            };
+
+            spans.push(Span::styled(format!("  target {:>6.2}%", target), Style::default().fg(theme.label)));
+            spans.push(Span::styled(format!("  drift {:>+7.2}", drift), style));
+            spans.push(Span::styled(format!("  {} {:.2}", action, trade.abs()), style));
+        }
+
+        Spans::from(spans)
+    }).collect();
+
+    if lines.is_empty() {
+        lines.push(Spans::from(Span::styled(
+            "No positions configured, add a [portfolio] section to the configuration",
+            Style::default().fg(theme.label),
+        )));
+    }
+
+    let title = format!("Allocation by {} (g: next grouping, total {:.2})", grouping, allocation.total);
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default()
+            .title(Span::styled(title, Style::default().fg(theme.title).add_modifier(Modifier::BOLD)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+        )
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
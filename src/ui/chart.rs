@@ -0,0 +1,227 @@
+use crate::{
+    app::GraphType,
+    portfolio::ValueHistory,
+    ticker::Ticker,
+    ui::theme::Theme,
+};
+use ordered_float::OrderedFloat;
+use std::borrow::Cow;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Modifier, Style},
+    symbols,
+    text::Span,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType as TuiGraphType},
+    Frame,
+};
+
+/// Draws the main chart of `ticker`, or of the portfolio `history`, with
+/// `status` appended to the title.
+pub fn render<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    ticker: &Ticker,
+    graph_type: &GraphType,
+    history: &ValueHistory,
+    status: &str,
+    theme: &Theme,
+) {
+    let p = &OrderedFloat::from(0.0);
+
+    // borrowed where possible, long histories are redrawn on every update
+    let (data, y): (Cow<[OrderedFloat<f64>]>, Cow<[String]>) = match graph_type {
+        GraphType::Price => (Cow::Borrowed(ticker.data().price_data()), Cow::Borrowed(ticker.date_data())),
+        GraphType::Volume => (Cow::Owned(ticker.volume_data_f64()), Cow::Borrowed(ticker.date_data())),
+        GraphType::Portfolio => (
+            history.value.iter().map(|v| OrderedFloat::from(*v)).collect(),
+            Cow::Owned(history.labels()),
+        ),
+    };
+
+    // two Braille dots per cell, more points than that only cost time
+    let buckets = area.width as usize * 2;
+
+    let len = data.len();
+    let floats = downsample(data.iter().enumerate()
+        .map(|(idx, &elem)| (idx as f64 + 1.0, f64::from(elem)))
+        .collect(), buckets);
+
+    let cost_floats = downsample(history.cost.iter().enumerate()
+        .map(|(idx, &elem)| (idx as f64 + 1.0, elem))
+        .collect(), buckets);
+
+    let cost_min = history.cost.iter().cloned().fold(f64::INFINITY, f64::min);
+    let cost_max = history.cost.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    let min = f64::from(*data.iter().min().unwrap_or(p)).min(cost_min);
+    let max = f64::from(*data.iter().max().unwrap_or(p)).max(cost_max);
+
+    let f_date = y.first().cloned().unwrap_or_default();
+    let m_date = y.get(y.len() / 2).cloned().unwrap_or_default();
+    let l_date = y.last().cloned().unwrap_or_default();
+
+    let series_name = match graph_type {
+        GraphType::Portfolio => format!("Value ({})", history.currency),
+        _ => ticker.identifier().clone(),
+    };
+
+    let mut datasets = vec![
+        Dataset::default()
+            .name(series_name)
+            .marker(symbols::Marker::Braille)
+            .graph_type(TuiGraphType::Line)
+            .style(Style::default().fg(theme.price))
+            .data(&floats),
+    ];
+
+    if let GraphType::Portfolio = graph_type {
+        datasets.push(Dataset::default()
+            .name("Cost basis")
+            .marker(symbols::Marker::Braille)
+            .graph_type(TuiGraphType::Line)
+            .style(Style::default().fg(theme.label))
+            .data(&cost_floats));
+    }
+
+    let title = match graph_type {
+        GraphType::Price => "Price",
+        GraphType::Volume => "Volume",
+        GraphType::Portfolio => "Value",
+    };
+
+    let subject = match graph_type {
+        GraphType::Portfolio => "Portfolio",
+        _ => ticker.identifier(),
+    };
+
+    let chart = Chart::new(datasets)
+        .block(Block::default()
+               .title(Span::styled(
+                    format!("TUInance - {} ({}) {}", subject, ticker.period(), status),
+                    Style::default()
+                    .fg(theme.title)
+                    .add_modifier(Modifier::BOLD))
+                )
+               .borders(Borders::ALL)
+               .border_style(Style::default().fg(theme.border))
+        )
+        .style(Style::default().fg(theme.text))
+        .x_axis(Axis::default()
+            .title(Span::styled(
+                "Date",
+                Style::default().fg(theme.title)
+            ))
+            .style(Style::default().fg(theme.border))
+            .bounds([0.0, len as f64])
+            .labels([f_date, m_date, l_date]
+                .iter()
+                .cloned()
+                .map(|x| Span::styled(x, Style::default().fg(theme.title)))
+                .collect()
+            )
+        )
+
+        .y_axis(Axis::default()
+            .title(Span::styled(title, Style::default().fg(theme.title)))
+            .style(Style::default().fg(theme.border))
+            .bounds([min, max])
+            .labels([format!("{:.3}", min), format!("{:.3}", min + (max - min) / 2.0), format!("{:.3}", max)]
+                .iter()
+                .cloned()
+                .map(|x| Span::styled(x, Style::default().fg(theme.title)))
+                .collect()
+            )
+        );
+
+    f.render_widget(chart, area);
+}
+
+/// Reduces `points` to the lowest and highest point of each of `buckets`
+/// equal slices, keeping their order, so peaks and troughs survive plotting
+/// at terminal resolution. The last point is always kept.
+pub fn downsample(points: Vec<(f64, f64)>, buckets: usize) -> Vec<(f64, f64)> {
+    let len = points.len();
+
+    if buckets == 0 || len <= buckets * 2 {
+        return points;
+    }
+
+    let mut out = Vec::with_capacity(buckets * 2 + 1);
+
+    for bucket in 0..buckets {
+        let start = bucket * len / buckets;
+        let end = (bucket + 1) * len / buckets;
+
+        let (mut low, mut high) = (start, start);
+
+        for (idx, point) in points.iter().enumerate().take(end).skip(start) {
+            if point.1 < points[low].1 {
+                low = idx;
+            }
+
+            if point.1 > points[high].1 {
+                high = idx;
+            }
+        }
+
+        out.push(points[low.min(high)]);
+
+        if low != high {
+            out.push(points[low.max(high)]);
+        }
+    }
+
+    if out.last() != points.last() {
+        out.push(points[len - 1]);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A wave with a single global low at x = 123 and high at x = 456.
+    fn points() -> Vec<(f64, f64)> {
+        (0..1000).map(|x| {
+            let y = match x {
+                123 => -10.0,
+                456 => 10.0,
+                _ => (x as f64 / 10.0).sin(),
+            };
+
+            (x as f64, y)
+        }).collect()
+    }
+
+    #[test]
+    fn downsample_keeps_the_extremes_in_order() {
+        let out = downsample(points(), 20);
+
+        assert!(out.len() <= 20 * 2 + 1);
+        assert!(out.contains(&(123.0, -10.0)));
+        assert!(out.contains(&(456.0, 10.0)));
+        assert!(out.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn downsample_ends_with_the_last_point() {
+        for buckets in [1, 3, 7, 20, 333] {
+            assert_eq!(downsample(points(), buckets).last(), Some(&(999.0, (99.9f64).sin())));
+        }
+
+        let rising: Vec<(f64, f64)> = (0..100).map(|x| (x as f64, x as f64)).collect();
+        assert_eq!(downsample(rising, 10).last(), Some(&(99.0, 99.0)));
+    }
+
+    #[test]
+    fn downsample_keeps_short_input() {
+        let short: Vec<(f64, f64)> = points().into_iter().take(40).collect();
+
+        assert_eq!(downsample(short.clone(), 20), short);
+        assert_eq!(downsample(points(), 0), points());
+        assert_eq!(downsample(vec![], 5), vec![]);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    correlation::{Matrix, ROLLING_WINDOW},
+    ui::theme::Theme,
+};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph},
+    Frame,
+};
+
+const LABEL_WIDTH: usize = 10;
+const CELL_WIDTH: usize = 8;
+
+/// Draws the correlation heatmap with the `selected` (row, column) cell
+/// highlighted, and the rolling correlation of that pair below it.
+pub fn render<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    matrix: &Matrix,
+    selected: (usize, usize),
+    period: &str,
+    theme: &Theme,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length((matrix.len() as u16 + 3).min(area.height / 2)),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let mut header = vec![Span::raw(" ".repeat(LABEL_WIDTH))];
+    header.extend(matrix.symbols.iter().enumerate().map(|(col, symbol)| {
+        let style = match col == selected.1 {
+            true => Style::default().fg(theme.selected).add_modifier(Modifier::BOLD),
+            false => Style::default().fg(theme.title),
+        };
+
+        Span::styled(format!("{:>width$.width$}", symbol, width = CELL_WIDTH - 1) + " ", style)
+    }));
+
+    let mut lines = vec![Spans::from(header)];
+
+    for (row, symbol) in matrix.symbols.iter().enumerate() {
+        let style = match row == selected.0 {
+            true => Style::default().fg(theme.selected).add_modifier(Modifier::BOLD),
+            false => Style::default().fg(theme.title),
+        };
+
+        let mut spans = vec![Span::styled(format!("{:<width$.width$}", symbol, width = LABEL_WIDTH), style)];
+
+        spans.extend((0..matrix.len()).map(|col| {
+            let (text, bg) = match matrix.get(row, col) {
+                Some(c) => (format!("{:>+width$.2} ", c, width = CELL_WIDTH - 1), theme.heat(c)),
+                None => (format!("{:>width$} ", "-", width = CELL_WIDTH - 1), Color::Reset),
+            };
+
+            let style = match (row, col) == selected {
+                true => Style::default().fg(theme.selected).bg(bg).add_modifier(Modifier::BOLD | Modifier::REVERSED),
+                false => Style::default().fg(theme.text).bg(bg),
+            };
+
+            Span::styled(text, style)
+        }));
+
+        lines.push(Spans::from(spans));
+    }
+
+    let heatmap = Paragraph::new(lines)
+        .block(Block::default()
+            .title(Span::styled(
+                format!("Correlation of daily returns ({}) (h/j/k/l: select a pair, c: close)", period),
+                Style::default().fg(theme.title).add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+        )
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(heatmap, chunks[0]);
+
+    let pair = match (matrix.symbols.get(selected.0), matrix.symbols.get(selected.1)) {
+        (Some(a), Some(b)) => format!("{} / {}", a, b),
+        _ => String::new(),
+    };
+
+    let rolling = matrix.rolling(selected.0, selected.1, ROLLING_WINDOW);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!("Rolling {}-day correlation {}", ROLLING_WINDOW, pair),
+            Style::default().fg(theme.title).add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border));
+
+    if rolling.is_empty() {
+        let message = Paragraph::new(Span::styled(
+            format!("Not enough common dates, select a longer interval (more than {} days)", ROLLING_WINDOW),
+            Style::default().fg(theme.label),
+        ))
+        .block(block);
+
+        f.render_widget(message, chunks[1]);
+        return;
+    }
+
+    let points: Vec<(f64, f64)> = rolling.iter().enumerate().map(|(idx, (_, c))| (idx as f64, *c)).collect();
+
+    let date = |idx: usize| rolling.get(idx).map(|(d, _)| d.format("%b %e %Y").to_string()).unwrap_or_default();
+
+    let datasets = vec![
+        Dataset::default()
+            .name(pair)
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme.price))
+            .data(&points),
+    ];
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .style(Style::default().fg(theme.text))
+        .x_axis(Axis::default()
+            .style(Style::default().fg(theme.border))
+            .bounds([0.0, (points.len() - 1).max(1) as f64])
+            .labels([date(0), date(rolling.len() / 2), date(rolling.len() - 1)]
+                .iter()
+                .cloned()
+                .map(|x| Span::styled(x, Style::default().fg(theme.title)))
+                .collect()
+            )
+        )
+        .y_axis(Axis::default()
+            .style(Style::default().fg(theme.border))
+            .bounds([-1.0, 1.0])
+            .labels(["-1.00", "0.00", "1.00"]
+                .iter()
+                .map(|x| Span::styled(*x, Style::default().fg(theme.title)))
+                .collect()
+            )
+        );
+
+    f.render_widget(chart, chunks[1]);
+}
@@ -1,19 +1,19 @@
 pub mod key;
 pub use key::Key;
 
-use crossterm::event;
-use std::{sync::mpsc, thread, time::Duration};
+use crossterm::event::{self, EventStream};
+use futures::StreamExt;
+use std::time::Duration;
+use tokio::time::{self, Interval};
 
 #[derive(Debug, Clone, Copy)]
 pub struct EventConfig {
-    pub exit_key: Key,
     pub tick_rate: Duration,
 }
 
 impl Default for EventConfig {
     fn default() -> EventConfig {
         EventConfig {
-            exit_key: Key::Char('q'),
             tick_rate: Duration::from_millis(250),
         }
     }
@@ -24,47 +24,38 @@ pub enum Event<I> {
     Tick,
 }
 
+/// Key presses read asynchronously from the terminal, interleaved with ticks.
 pub struct Events {
-    rx: mpsc::Receiver<Event<Key>>,
-    _tx: mpsc::Sender<Event<Key>>,
+    stream: EventStream,
+    ticks: Interval,
 }
 
 impl Events {
     pub fn new(tick_rate: u64) -> Events {
         Events::with_config(EventConfig {
             tick_rate: Duration::from_millis(tick_rate),
-            ..Default::default()
         })
     }
 
+    /// Must be called from within the tokio runtime.
     pub fn with_config(config: EventConfig) -> Events {
-        let (tx, rx) = mpsc::channel();
-
-        let event_tx = tx.clone();
-
-        thread::spawn(move || {
-            loop {
-                if event::poll(config.tick_rate).unwrap() {
-                    if let event::Event::Key(key) = event::read().unwrap() {
-                        let key = Key::from(key);
-
-
-                        event_tx.send(Event::Input(key)).unwrap();
-
-                        if let Key::Char('q') = key {
-                            break;
-                        }
-                    }
-                }
-
-                event_tx.send(Event::Tick).unwrap();
-            }
-        });
-
-        Events { rx, _tx: tx }
+        Events {
+            stream: EventStream::new(),
+            ticks: time::interval(config.tick_rate),
+        }
     }
 
-    pub fn next(&self) -> Result<Event<Key>, mpsc::RecvError> {
-        self.rx.recv()
+    /// The next key press or tick, `None` once the terminal input is closed.
+    pub async fn next(&mut self) -> Option<Event<Key>> {
+        loop {
+            tokio::select! {
+                event = self.stream.next() => match event {
+                    Some(Ok(event::Event::Key(key))) => return Some(Event::Input(Key::from(key))),
+                    Some(Ok(_)) => (),
+                    Some(Err(_)) | None => return None,
+                },
+                _ = self.ticks.tick() => return Some(Event::Tick),
+            }
+        }
     }
 }
@@ -0,0 +1,341 @@
+use crate::{portfolio::Summary, quote::Quote, ticker::Ticker, ui::theme::Theme};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Daily change, in percent, at which a tile reaches its full color.
+const FULL_SCALE: f64 = 3.0;
+
+/// Share of the total given to tickers with a smaller value, or none at all,
+/// so that they keep a tile.
+const MIN_WEIGHT: f64 = 0.02;
+
+/// What the tiles of the market map are sized by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sizing {
+    Equal,
+    /// Market value of the portfolio holdings.
+    Value,
+    MarketCap,
+}
+
+impl Sizing {
+    pub const ALL: [Sizing; 3] = [Sizing::Value, Sizing::MarketCap, Sizing::Equal];
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Sizing::Equal => "equal weight",
+            Sizing::Value => "position value",
+            Sizing::MarketCap => "market cap",
+        }
+    }
+}
+
+/// Tile weights: the market value held or the market cap of each ticker, at
+/// least `MIN_WEIGHT` of the total, or equal weights when no ticker has a
+/// value. Values in different currencies are compared as is.
+pub fn weights(tickers: &[Ticker], summary: &Summary, sizing: Sizing) -> Vec<f64> {
+    let held = |symbol: &String| -> f64 {
+        summary.holdings.iter().filter(|h| &h.symbol == symbol).map(|h| h.market_value.max(0.0)).sum()
+    };
+
+    let values: Vec<f64> = match sizing {
+        Sizing::Equal => vec![0.0; tickers.len()],
+        Sizing::Value => tickers.iter().map(|t| held(t.identifier())).collect(),
+        Sizing::MarketCap => tickers.iter().map(|t| t.market_cap().unwrap_or(0.0).max(0.0)).collect(),
+    };
+
+    proportional(values)
+}
+
+fn proportional(values: Vec<f64>) -> Vec<f64> {
+    let minimum = values.iter().sum::<f64>() * MIN_WEIGHT;
+
+    match minimum > 0.0 {
+        true => values.into_iter().map(|v| v.max(minimum)).collect(),
+        false => vec![1.0; values.len()],
+    }
+}
+
+/// Splits `area` into one tile per weight, each sized in proportion to its
+/// weight. Tiles of zero weight are empty rectangles.
+pub fn tiles(area: Rect, weights: &[f64]) -> Vec<Rect> {
+    let mut items: Vec<(usize, f64)> = weights
+        .iter()
+        .cloned()
+        .enumerate()
+        .filter(|(_, w)| *w > 0.0 && w.is_finite())
+        .collect();
+
+    items.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut out = vec![Rect::default(); weights.len()];
+    split(&items, area, &mut out);
+    out
+}
+
+/// Halves the items by weight and the area along its longer side, until one
+/// item is left per part. Cells are about twice as tall as they are wide.
+fn split(items: &[(usize, f64)], area: Rect, out: &mut Vec<Rect>) {
+    match items.len() {
+        0 => return,
+        1 => {
+            out[items[0].0] = area;
+            return;
+        }
+        _ => (),
+    }
+
+    let total: f64 = items.iter().map(|(_, w)| w).sum();
+
+    let mut prefix = 0.0;
+    let mut best = (1, f64::INFINITY);
+
+    for (idx, (_, w)) in items[..items.len() - 1].iter().enumerate() {
+        prefix += w;
+
+        if (2.0 * prefix - total).abs() < best.1 {
+            best = (idx + 1, (2.0 * prefix - total).abs());
+        }
+    }
+
+    let (first, second) = items.split_at(best.0);
+    let fraction = first.iter().map(|(_, w)| w).sum::<f64>() / total;
+
+    let part = |length: u16| ((length as f64 * fraction).round() as u16).max(1).min(length.saturating_sub(1));
+
+    let (a, b) = match area.width as f64 >= area.height as f64 * 2.0 {
+        true => {
+            let width = part(area.width);
+            (
+                Rect { width, ..area },
+                Rect { x: area.x + width, width: area.width - width, ..area },
+            )
+        }
+        false => {
+            let height = part(area.height);
+            (
+                Rect { height, ..area },
+                Rect { y: area.y + height, height: area.height - height, ..area },
+            )
+        }
+    };
+
+    split(first, a, out);
+    split(second, b, out);
+}
+
+/// `selected` if its tile has room, otherwise the first tile that has.
+pub fn visible(tiles: &[Rect], selected: usize) -> usize {
+    match tiles.get(selected).map(|r| r.area() > 0) {
+        Some(true) => selected,
+        _ => tiles.iter().position(|r| r.area() > 0).unwrap_or(selected),
+    }
+}
+
+/// The tile next to `selected` in the direction `(dx, dy)`: the closest one
+/// lying entirely beyond its edge, or `selected` itself at the border.
+pub fn neighbour(tiles: &[Rect], selected: usize, (dx, dy): (i32, i32)) -> usize {
+    let from = match tiles.get(selected) {
+        Some(r) => *r,
+        None => return selected,
+    };
+
+    let beyond = |r: &Rect| match (dx.signum(), dy.signum()) {
+        (1, _) => r.x >= from.right(),
+        (-1, _) => r.right() <= from.x,
+        (_, 1) => r.y >= from.bottom(),
+        (_, -1) => r.bottom() <= from.y,
+        _ => false,
+    };
+
+    // cells are about twice as tall as they are wide
+    let distance = |r: &Rect| {
+        let x = (r.x as f64 + r.width as f64 / 2.0) - (from.x as f64 + from.width as f64 / 2.0);
+        let y = (r.y as f64 + r.height as f64 / 2.0) - (from.y as f64 + from.height as f64 / 2.0);
+        (x / 2.0).hypot(y)
+    };
+
+    tiles
+        .iter()
+        .enumerate()
+        .filter(|(idx, r)| *idx != selected && r.area() > 0 && beyond(r))
+        .min_by(|(_, a), (_, b)| distance(a).partial_cmp(&distance(b)).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(idx, _)| idx)
+        .unwrap_or(selected)
+}
+
+/// Draws every ticker as a tile colored by its daily change, sized by
+/// `weights`, with `selected` outlined.
+pub fn render<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    tickers: &[Ticker],
+    weights: &[f64],
+    selected: usize,
+    sizing: Sizing,
+    theme: &Theme,
+) {
+    let block = Block::default()
+        .title(Span::styled(
+            format!("Market map by {} (h/j/k/l: move, Enter: chart, g: sizing, m: close)", sizing.label()),
+            Style::default().fg(theme.title).add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border));
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    for (idx, (ticker, tile)) in tickers.iter().zip(tiles(inner, weights)).enumerate() {
+        if tile.area() == 0 {
+            continue;
+        }
+
+        let quote = Quote::from(ticker);
+        let change = quote.change_percent;
+
+        let style = Style::default()
+            .fg(theme.text)
+            .bg(theme.heat(change.unwrap_or(0.0) / FULL_SCALE));
+
+        let lines = vec![
+            Spans::from(Span::styled(quote.symbol.clone(), Style::default().add_modifier(Modifier::BOLD))),
+            Spans::from(change.map(|c| format!("{:+.2}%", c)).unwrap_or_else(|| "-".into())),
+            Spans::from(match quote.price == 0.0 {
+                true => "-".to_string(),
+                false => format!("{:.2}", quote.price),
+            }),
+        ];
+
+        let mut paragraph = Paragraph::new(lines).style(style).alignment(Alignment::Center);
+
+        // leave room for the text when outlining small tiles
+        if tile.width >= 5 && tile.height >= 3 {
+            let border = match idx == selected {
+                true => Style::default().fg(theme.selected).add_modifier(Modifier::BOLD),
+                false => Style::default().fg(theme.border),
+            };
+
+            paragraph = paragraph.block(Block::default().borders(Borders::ALL).border_style(border));
+        } else if idx == selected {
+            paragraph = paragraph.style(style.add_modifier(Modifier::REVERSED));
+        }
+
+        f.render_widget(paragraph, tile);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: u16, y: u16, width: u16, height: u16) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    /// A 2x2 grid: 0 1 on top, 2 3 below.
+    fn grid() -> Vec<Rect> {
+        vec![rect(0, 0, 10, 5), rect(10, 0, 10, 5), rect(0, 5, 10, 5), rect(10, 5, 10, 5)]
+    }
+
+    #[test]
+    fn weights_fall_back_to_equal() {
+        assert_eq!(proportional(vec![0.0, 0.0, 0.0]), vec![1.0, 1.0, 1.0]);
+        assert_eq!(proportional(vec![]), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn weights_keep_a_minimum_share() {
+        assert_eq!(proportional(vec![90.0, 10.0, 0.0]), vec![90.0, 10.0, 2.0]);
+        assert_eq!(proportional(vec![99.0, 1.0]), vec![99.0, 2.0]);
+    }
+
+    #[test]
+    fn sizing_cycles() {
+        assert_eq!(Sizing::Value.next(), Sizing::MarketCap);
+        assert_eq!(Sizing::MarketCap.next(), Sizing::Equal);
+        assert_eq!(Sizing::Equal.next(), Sizing::Value);
+    }
+
+    #[test]
+    fn tiles_are_proportional() {
+        // wide areas are split side by side
+        assert_eq!(tiles(rect(0, 0, 40, 10), &[1.0, 3.0]), vec![rect(30, 0, 10, 10), rect(0, 0, 30, 10)]);
+        // tall ones, counting cells as twice as tall as wide, on top of each other
+        assert_eq!(tiles(rect(0, 0, 10, 10), &[1.0, 1.0]), vec![rect(0, 0, 10, 5), rect(0, 5, 10, 5)]);
+    }
+
+    #[test]
+    fn tiles_skip_empty_weights() {
+        let tiles = tiles(rect(0, 0, 40, 10), &[1.0, 0.0, f64::NAN, 1.0]);
+
+        assert_eq!(tiles[1], Rect::default());
+        assert_eq!(tiles[2], Rect::default());
+        assert_eq!(tiles[0].area() + tiles[3].area(), 400);
+    }
+
+    #[test]
+    fn split_covers_the_area_without_overlap() {
+        let items: Vec<(usize, f64)> = [5.0, 3.0, 2.0, 1.0, 1.0].iter().cloned().enumerate().collect();
+        let mut out = vec![Rect::default(); items.len()];
+        split(&items, rect(2, 1, 60, 20), &mut out);
+
+        assert_eq!(out.iter().map(|r| r.area()).sum::<u16>(), 60 * 20);
+
+        for (idx, a) in out.iter().enumerate() {
+            assert!(a.x >= 2 && a.y >= 1 && a.right() <= 62 && a.bottom() <= 21);
+            assert!(out[idx + 1..].iter().all(|b| !a.intersects(*b)));
+        }
+    }
+
+    #[test]
+    fn neighbour_moves_across_the_grid() {
+        let tiles = grid();
+
+        assert_eq!(neighbour(&tiles, 0, (1, 0)), 1);
+        assert_eq!(neighbour(&tiles, 0, (0, 1)), 2);
+        assert_eq!(neighbour(&tiles, 3, (-1, 0)), 2);
+        assert_eq!(neighbour(&tiles, 3, (0, -1)), 1);
+    }
+
+    #[test]
+    fn neighbour_stays_at_the_border() {
+        let tiles = grid();
+
+        assert_eq!(neighbour(&tiles, 0, (-1, 0)), 0);
+        assert_eq!(neighbour(&tiles, 0, (0, -1)), 0);
+        assert_eq!(neighbour(&tiles, 3, (1, 0)), 3);
+        assert_eq!(neighbour(&tiles, 9, (1, 0)), 9);
+    }
+
+    #[test]
+    fn neighbour_skips_empty_tiles() {
+        let mut tiles = grid();
+        tiles[1] = Rect::default();
+
+        assert_eq!(neighbour(&tiles, 0, (1, 0)), 3);
+    }
+
+    #[test]
+    fn visible_keeps_or_replaces_the_selection() {
+        let mut tiles = grid();
+        assert_eq!(visible(&tiles, 2), 2);
+
+        tiles[0] = Rect::default();
+        tiles[2] = Rect::default();
+        assert_eq!(visible(&tiles, 2), 1);
+        assert_eq!(visible(&tiles, 7), 1);
+        assert_eq!(visible(&[Rect::default()], 0), 0);
+    }
+}
@@ -1,3 +1,13 @@
+pub mod alerts;
+pub mod allocation;
+pub mod chart;
+pub mod correlation;
 pub mod event;
+pub mod heatmap;
+pub mod portfolio;
+pub mod prompt;
+pub mod stats;
+pub mod theme;
 pub mod utils;
 pub mod view;
+pub mod watchlist;
@@ -0,0 +1,172 @@
+use crate::{
+    ledger::Book,
+    portfolio::{Summary, ValueHistory},
+    ui::theme::Theme,
+};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+const WIDTHS: [Constraint; 11] = [
+    Constraint::Length(8),
+    Constraint::Min(12),
+    Constraint::Length(10),
+    Constraint::Length(10),
+    Constraint::Length(12),
+    Constraint::Length(12),
+    Constraint::Length(12),
+    Constraint::Length(8),
+    Constraint::Length(10),
+    Constraint::Length(8),
+    Constraint::Length(7),
+];
+
+pub fn render<B: Backend>(f: &mut Frame<B>, area: Rect, summary: &Summary, theme: &Theme) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Min(3),
+            Constraint::Length(summary.totals.len().max(1) as u16 + 2),
+        ])
+        .split(area);
+
+    let header = Row::new(vec![
+        "Symbol", "Name", "Shares", "Price", "Value", "Cost", "Gain", "Gain %", "Day", "Day %", "Weight",
+    ])
+    .style(Style::default().fg(theme.title).add_modifier(Modifier::BOLD))
+    .bottom_margin(1);
+
+    let rows: Vec<Row> = summary.holdings.iter().map(|h| {
+        let gain = Style::default().fg(theme.change(h.gain));
+        let day = Style::default().fg(theme.change(h.day_change));
+
+        Row::new(vec![
+            Cell::from(h.symbol.clone()).style(Style::default().fg(theme.selected)),
+            Cell::from(h.name.clone()),
+            Cell::from(format!("{:.2}", h.shares)),
+            Cell::from(format!("{:.2}", h.price)),
+            Cell::from(format!("{:.2}", h.market_value)),
+            Cell::from(format!("{:.2}", h.cost_basis)),
+            Cell::from(format!("{:+.2}", h.gain)).style(gain),
+            Cell::from(format!("{:+.2}%", h.gain_pct)).style(gain),
+            Cell::from(format!("{:+.2}", h.day_change)).style(day),
+            Cell::from(format!("{:+.2}%", h.day_change_pct)).style(day),
+            Cell::from(format!("{:.1}%", h.weight)),
+        ])
+    }).collect();
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default()
+            .title(Span::styled("Portfolio", Style::default().fg(theme.title).add_modifier(Modifier::BOLD)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+        )
+        .style(Style::default().fg(theme.text))
+        .widths(&WIDTHS)
+        .column_spacing(1);
+
+    let totals: Vec<Spans> = match summary.totals.is_empty() {
+        true => vec![Spans::from(Span::styled(
+            "No positions configured, add a [portfolio] section to the configuration",
+            Style::default().fg(theme.label),
+        ))],
+        false => summary.totals.iter().map(|t| {
+            Spans::from(vec![
+                Span::styled(format!("{} ", t.currency), Style::default().fg(theme.title)),
+                Span::styled("Value: ", Style::default().fg(theme.label)),
+                Span::raw(format!("{:.2}  ", t.market_value)),
+                Span::styled("Cost: ", Style::default().fg(theme.label)),
+                Span::raw(format!("{:.2}  ", t.cost_basis)),
+                Span::styled("Gain: ", Style::default().fg(theme.label)),
+                Span::styled(
+                    format!("{:+.2} ({:+.2}%)  ", t.gain, t.gain_pct),
+                    Style::default().fg(theme.change(t.gain)),
+                ),
+                Span::styled("Day: ", Style::default().fg(theme.label)),
+                Span::styled(
+                    format!("{:+.2} ({:+.2}%)", t.day_change, t.day_change_pct),
+                    Style::default().fg(theme.change(t.day_change)),
+                ),
+            ])
+        }).collect(),
+    };
+
+    let totals = Paragraph::new(totals)
+        .block(Block::default()
+            .title(Span::styled("Totals", Style::default().fg(theme.title).add_modifier(Modifier::BOLD)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+        )
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(table, chunks[0]);
+    f.render_widget(totals, chunks[1]);
+}
+
+/// Info pane lines listing the open lots and realized gain of `symbol`.
+pub fn lot_spans(book: &Book, symbol: &str, theme: &Theme) -> Vec<Spans<'static>> {
+    let holding = match book.holding(symbol) {
+        Some(holding) => holding,
+        None => return vec![],
+    };
+
+    let realized = book.realized_gain(symbol);
+
+    let mut spans = vec![
+        Spans::default(),
+        Spans::from(vec![
+            Span::styled(format!("Lots ({}): ", book.method), Style::default().fg(theme.label)),
+            Span::styled(
+                format!("{:.2} @ {:.2}", holding.shares(), holding.cost_basis() / holding.shares().max(f64::EPSILON)),
+                Style::default().fg(theme.title),
+            ),
+        ]),
+    ];
+
+    for lot in holding.lots.iter() {
+        spans.push(Spans::from(Span::raw(format!(
+            "  {}  {:.2} @ {:.2}",
+            lot.date.format("%Y-%m-%d"), lot.shares, lot.cost
+        ))));
+    }
+
+    spans.push(Spans::from(vec![
+        Span::styled("Realized: ", Style::default().fg(theme.label)),
+        Span::styled(format!("{:+.2}", realized), Style::default().fg(theme.change(realized))),
+    ]));
+
+    if holding.dividends != 0.0 || holding.fees != 0.0 {
+        spans.push(Spans::from(vec![
+            Span::styled("Dividends: ", Style::default().fg(theme.label)),
+            Span::raw(format!("{:.2}  ", holding.dividends)),
+            Span::styled("Fees: ", Style::default().fg(theme.label)),
+            Span::raw(format!("{:.2}", holding.fees)),
+        ]));
+    }
+
+    spans
+}
+
+/// Info pane lines with the time- and money-weighted returns of the plotted history.
+pub fn return_spans(history: &ValueHistory, theme: &Theme) -> Vec<Spans<'static>> {
+    let line = |label: &'static str, value: Option<f64>, suffix: &'static str| {
+        let value = match value {
+            Some(v) => Span::styled(format!("{:+.2}%{}", v * 100.0, suffix), Style::default().fg(theme.change(v))),
+            None => Span::raw("-"),
+        };
+
+        Spans::from(vec![Span::styled(label, Style::default().fg(theme.label)), value])
+    };
+
+    vec![
+        Spans::default(),
+        line("Time-weighted: ", history.time_weighted_return(), ""),
+        line("Money-weighted: ", history.money_weighted_return(), " p.a."),
+    ]
+}
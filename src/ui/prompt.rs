@@ -0,0 +1,38 @@
+use crate::{app::Prompt, ui::theme::Theme};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Draws the prompt line, and its last error, along the bottom of `area`.
+pub fn render<B: Backend>(f: &mut Frame<B>, area: Rect, prompt: &Prompt, theme: &Theme) {
+    let height = 4.min(area.height);
+    let rect = Rect::new(area.x, area.y + area.height - height, area.width, height);
+
+    let mut text = vec![Spans::from(vec![
+        Span::styled(prompt.label(), Style::default().fg(theme.label)),
+        Span::styled(format!("{}_", prompt.input()), Style::default().fg(theme.selected)),
+    ])];
+
+    if let Some(error) = prompt.error() {
+        text.push(Spans::from(Span::styled(error.clone(), Style::default().fg(theme.down))));
+    }
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default()
+            .title(Span::styled(
+                format!("{} (Enter: confirm, Esc: cancel)", prompt.hint()),
+                Style::default().fg(theme.title),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+        )
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(Clear, rect);
+    f.render_widget(paragraph, rect);
+}
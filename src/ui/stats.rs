@@ -0,0 +1,86 @@
+use crate::{risk::Risk, stats::Statistics, ui::theme::Theme};
+use tui::{
+    style::Style,
+    text::{Span, Spans},
+};
+
+/// Info pane lines with the statistics of the charted bars.
+pub fn spans(stats: &Statistics, theme: &Theme) -> Vec<Spans<'static>> {
+    let label = |s: &'static str| Span::styled(s, Style::default().fg(theme.label));
+    let percent = |v: Option<f64>| v.map(|v| format!("{:.2}%", v * 100.0)).unwrap_or_else(|| "-".into());
+
+    let change = stats.close - stats.open;
+
+    let mut spans = vec![
+        Spans::default(),
+        Spans::from(vec![
+            label("Open/Close: "),
+            Span::raw(format!("{:.2} / {:.2} ", stats.open, stats.close)),
+            Span::styled(format!("({:+.2})", change), Style::default().fg(theme.change(change))),
+        ]),
+        Spans::from(vec![
+            label("High: "),
+            Span::raw(format!("{:.2} ({})", stats.high.0, stats.high.1.format("%Y-%m-%d"))),
+        ]),
+        Spans::from(vec![
+            label("Low: "),
+            Span::raw(format!("{:.2} ({})", stats.low.0, stats.low.1.format("%Y-%m-%d"))),
+        ]),
+        Spans::from(vec![
+            label("Mean/Median: "),
+            Span::raw(format!("{:.2} / {:.2}", stats.mean, stats.median)),
+        ]),
+        Spans::from(vec![label("Daily return SD: "), Span::raw(percent(stats.stddev))]),
+        Spans::from(vec![label("Volatility (ann.): "), Span::raw(percent(stats.volatility))]),
+    ];
+
+    spans.push(Spans::from(match stats.max_drawdown {
+        Some((drawdown, peak, trough)) => vec![
+            label("Max drawdown: "),
+            Span::styled(format!("{:.2}%", drawdown * 100.0), Style::default().fg(theme.down)),
+            Span::raw(format!(" ({} to {})", peak.format("%Y-%m-%d"), trough.format("%Y-%m-%d"))),
+        ],
+        None => vec![label("Max drawdown: "), Span::raw("-")],
+    }));
+
+    spans.push(Spans::from(vec![
+        label("Avg volume: "),
+        Span::raw(format!("{:.0}", stats.average_volume)),
+    ]));
+
+    spans.push(Spans::from(vec![
+        label("Up/Down days: "),
+        Span::styled(stats.up_days.to_string(), Style::default().fg(theme.up)),
+        Span::raw(" / "),
+        Span::styled(stats.down_days.to_string(), Style::default().fg(theme.down)),
+    ]));
+
+    spans
+}
+
+/// Info pane lines with the risk metrics, measured against `benchmark` when
+/// one is configured.
+pub fn risk_spans(risk: &Risk, benchmark: Option<&str>, theme: &Theme) -> Vec<Spans<'static>> {
+    let label = |s: String| Span::styled(s, Style::default().fg(theme.label));
+    let number = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".into());
+
+    let mut spans = vec![Spans::default()];
+
+    if let Some(benchmark) = benchmark {
+        let alpha = match risk.alpha {
+            Some(alpha) => Span::styled(format!("{:+.2}%", alpha * 100.0), Style::default().fg(theme.change(alpha))),
+            None => Span::raw("-"),
+        };
+
+        spans.push(Spans::from(vec![label(format!("Beta vs {}: ", benchmark)), Span::raw(number(risk.beta))]));
+        spans.push(Spans::from(vec![label("Alpha (ann.): ".into()), alpha]));
+        spans.push(Spans::from(vec![label("Correlation: ".into()), Span::raw(number(risk.correlation))]));
+    }
+
+    spans.push(Spans::from(vec![
+        label("Sharpe/Sortino: ".into()),
+        Span::raw(format!("{} / {}", number(risk.sharpe), number(risk.sortino))),
+    ]));
+
+    spans
+}
@@ -0,0 +1,227 @@
+use tui::style::Color;
+
+/// Colors standing in for the terminal's default foreground and background.
+pub const DEFAULT_FG: (u8, u8, u8) = (0xd0, 0xd0, 0xd0);
+pub const DEFAULT_BG: (u8, u8, u8) = (0x1c, 0x1c, 0x1c);
+
+/// xterm's 16 base colors, in the order of their palette indices.
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub border: Color,
+    pub title: Color,
+    pub label: Color,
+    pub text: Color,
+    pub price: Color,
+    pub volume: Color,
+    pub selected: Color,
+    pub up: Color,
+    pub down: Color,
+    pub alert: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            border: Color::Rgb(53, 59, 69),
+            title: Color::Yellow,
+            label: Color::Blue,
+            text: Color::White,
+            price: Color::Green,
+            volume: Color::Magenta,
+            selected: Color::Yellow,
+            up: Color::Green,
+            down: Color::Red,
+            alert: Color::LightRed,
+        }
+    }
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 3] = ["default", "light", "mono"];
+
+    pub fn light() -> Self {
+        Self {
+            border: Color::Gray,
+            title: Color::Blue,
+            label: Color::Magenta,
+            text: Color::Black,
+            price: Color::Green,
+            volume: Color::Cyan,
+            selected: Color::Blue,
+            up: Color::Green,
+            down: Color::Red,
+            alert: Color::LightRed,
+        }
+    }
+
+    pub fn mono() -> Self {
+        Self {
+            border: Color::DarkGray,
+            title: Color::White,
+            label: Color::Gray,
+            text: Color::White,
+            price: Color::White,
+            volume: Color::Gray,
+            selected: Color::White,
+            up: Color::White,
+            down: Color::Gray,
+            alert: Color::White,
+        }
+    }
+
+    /// Overrides a single color by its configuration key, ignoring unknown keys.
+    pub fn set(&mut self, key: &str, color: Color) {
+        match key {
+            "border" => self.border = color,
+            "title" => self.title = color,
+            "label" => self.label = color,
+            "text" => self.text = color,
+            "price" => self.price = color,
+            "volume" => self.volume = color,
+            "selected" => self.selected = color,
+            "up" => self.up = color,
+            "down" => self.down = color,
+            "alert" => self.alert = color,
+            _ => (),
+        }
+    }
+
+    /// Color for a signed change: `up` for gains, `down` for losses.
+    pub fn change(&self, value: f64) -> Color {
+        match value < 0.0 {
+            true => self.down,
+            false => self.up,
+        }
+    }
+
+    /// Heatmap color for a value between -1 and 1, fading from `border` at 0
+    /// towards `down` for negative and `up` for positive values.
+    pub fn heat(&self, value: f64) -> Color {
+        let value = value.clamp(-1.0, 1.0);
+        let (r0, g0, b0) = rgb(self.border, DEFAULT_BG);
+        let (r1, g1, b1) = rgb(self.change(value), DEFAULT_FG);
+
+        let mix = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * value.abs()).round() as u8;
+
+        Color::Rgb(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "default" => Some(Self::default()),
+            "light" => Some(Self::light()),
+            "mono" => Some(Self::mono()),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a color name (`yellow`, `dark_gray`, ...) or a `#rrggbb` hex value.
+pub fn parse_color(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+
+        let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    let color = match s.to_lowercase().replace(['-', ' '], "_").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+
+    Some(color)
+}
+
+/// Resolves `color` to RGB, using xterm's palette for named and indexed colors.
+pub fn rgb(color: Color, default: (u8, u8, u8)) -> (u8, u8, u8) {
+    let index = match color {
+        Color::Reset => return default,
+        Color::Rgb(r, g, b) => return (r, g, b),
+        Color::Indexed(n) => n,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+    };
+
+    match index {
+        0..=15 => PALETTE[index as usize],
+        16..=231 => {
+            let level = |v: u8| match v {
+                0 => 0,
+                v => 55 + v * 40,
+            };
+
+            let n = index - 16;
+            (level(n / 36), level((n / 6) % 6), level(n % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_resolves_colors() {
+        assert_eq!(rgb(Color::Reset, DEFAULT_FG), DEFAULT_FG);
+        assert_eq!(rgb(Color::Red, DEFAULT_FG), (0xcd, 0x00, 0x00));
+        assert_eq!(rgb(Color::Indexed(16), DEFAULT_FG), (0, 0, 0));
+        assert_eq!(rgb(Color::Indexed(231), DEFAULT_FG), (255, 255, 255));
+        assert_eq!(rgb(Color::Indexed(244), DEFAULT_FG), (128, 128, 128));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{
+    alert::Alerts,
+    quote::Quote,
+    ticker::Ticker,
+    ui::theme::Theme,
+    watchlist::{Column, Watchlist},
+};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Row, Table, TableState},
+    Frame,
+};
+
+/// Draws the watchlist table, scrolled to keep the `selected` ticker in view.
+pub fn render<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    tickers: &[Ticker],
+    watchlist: &Watchlist,
+    selected: usize,
+    alerts: &Alerts,
+    theme: &Theme,
+) {
+    let columns = watchlist.columns();
+    let sort = watchlist.sort();
+    let order = watchlist.order(tickers);
+
+    let header = Row::new(columns.iter().map(|column| {
+        let arrow = match sort {
+            Some(sort) if sort.column == *column && sort.descending => "▼",
+            Some(sort) if sort.column == *column => "▲",
+            _ => "",
+        };
+
+        let title = format!("{}{}", column.title(), arrow);
+
+        Cell::from(align(column, title)).style(Style::default().fg(theme.title).add_modifier(Modifier::BOLD))
+    }));
+
+    let rows: Vec<Row> = order.iter().map(|idx| {
+        let ticker = &tickers[*idx];
+        let quote = Quote::from(ticker);
+
+        let style = match *idx == selected {
+            true => Style::default().fg(theme.selected).add_modifier(Modifier::BOLD),
+            false if alerts.is_triggered(ticker.identifier()) => Style::default().fg(theme.alert),
+            false => Style::default(),
+        };
+
+        Row::new(columns.iter().map(|column| {
+            let cell = Cell::from(align(column, column.cell(ticker, &quote)));
+
+            match (column, column.value(ticker, &quote)) {
+                (Column::Change, Some(v)) | (Column::ChangePercent, Some(v)) if *idx != selected => {
+                    cell.style(Style::default().fg(theme.change(v)))
+                }
+                _ => cell,
+            }
+        }))
+        .style(style)
+    }).collect();
+
+    let widths: Vec<Constraint> = columns
+        .iter()
+        .map(|c| c.width().map(Constraint::Length).unwrap_or(Constraint::Min(6)))
+        .collect();
+
+    let title = match sort {
+        Some(sort) => format!("Watchlist by {}", sort.column.title()),
+        None => "Watchlist".into(),
+    };
+
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&widths)
+        .column_spacing(1)
+        .block(Block::default()
+            .title(Span::styled(title, Style::default().fg(theme.title)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+        )
+        .style(Style::default().fg(theme.text));
+
+    let mut state = TableState::default();
+    state.select(order.iter().position(|idx| *idx == selected));
+
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+/// Right-aligns numbers within the column width.
+fn align(column: &Column, text: String) -> String {
+    match (column.is_numeric(), column.width()) {
+        (true, Some(width)) => format!("{:>width$}", text, width = width as usize),
+        _ => text,
+    }
+}
@@ -1,3 +1,5 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use std::path::{Path, PathBuf};
 use yahoo_finance::Interval;
 
 pub fn next_interval(curr: Interval) -> Interval {
@@ -17,6 +19,23 @@ pub fn next_interval(curr: Interval) -> Interval {
     }
 }
 
+pub fn parse_interval(s: &str) -> Option<Interval> {
+    use Interval::*;
+    match s.to_lowercase().as_str() {
+        "5d" => Some(_5d),
+        "1mo" => Some(_1mo),
+        "3mo" => Some(_3mo),
+        "6mo" => Some(_6mo),
+        "1y" => Some(_1y),
+        "2y" => Some(_2y),
+        "5y" => Some(_5y),
+        "10y" => Some(_10y),
+        "ytd" => Some(_ytd),
+        "max" => Some(_max),
+        _ => None,
+    }
+}
+
 pub fn previous_interval(curr: Interval) -> Interval {
     use Interval::*;
     match curr {
@@ -35,6 +54,28 @@ pub fn previous_interval(curr: Interval) -> Interval {
     }
 }
 
+/// Expands a leading `~/` and joins relative paths onto `base`.
+pub fn resolve_path(path: &str, base: Option<&Path>) -> PathBuf {
+    if let (Some(rest), Ok(home)) = (path.strip_prefix("~/"), std::env::var("HOME")) {
+        return PathBuf::from(home).join(rest);
+    }
+
+    let path = PathBuf::from(path);
+
+    match (path.is_relative(), base) {
+        (true, Some(base)) => base.join(path),
+        _ => path,
+    }
+}
+
+pub fn timestamp_to_date(ts: i64) -> NaiveDate {
+    Utc.timestamp_opt(ts, 0)
+        .single()
+        .unwrap_or_default()
+        .naive_utc()
+        .date()
+}
+
 /*pub fn interval_to_days(int: Interval) -> u32 {
     use Interval::*;
     match int {
@@ -0,0 +1,351 @@
+use crate::{quote::Quote, ticker::Ticker};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// A column of the watchlist table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    Symbol,
+    Name,
+    Last,
+    Change,
+    ChangePercent,
+    Volume,
+    /// Position of the last price within the 52-week range.
+    Range,
+    MarketCap,
+}
+
+impl Column {
+    pub const NAMES: [&'static str; 8] = [
+        "symbol", "name", "last", "change", "change_percent", "volume", "range_52w", "market_cap",
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Column::Symbol => "Symbol",
+            Column::Name => "Name",
+            Column::Last => "Last",
+            Column::Change => "Chg",
+            Column::ChangePercent => "Chg%",
+            Column::Volume => "Volume",
+            Column::Range => "52W",
+            Column::MarketCap => "Mkt Cap",
+        }
+    }
+
+    /// Width of the column in cells, `None` for the column taking the rest.
+    pub fn width(&self) -> Option<u16> {
+        match self {
+            Column::Symbol => Some(8),
+            Column::Name => None,
+            Column::Last => Some(10),
+            Column::Change => Some(8),
+            Column::ChangePercent => Some(7),
+            Column::Volume => Some(7),
+            Column::Range => Some(4),
+            Column::MarketCap => Some(8),
+        }
+    }
+
+    /// Whether the column holds numbers, which are right-aligned.
+    pub fn is_numeric(&self) -> bool {
+        !matches!(self, Column::Symbol | Column::Name)
+    }
+
+    /// Numeric value of the column, also used for sorting. `quote` is the
+    /// ticker's, built once for its whole row.
+    pub fn value(&self, ticker: &Ticker, quote: &Quote) -> Option<f64> {
+        match self {
+            Column::Symbol | Column::Name => None,
+            Column::Last => Some(quote.price).filter(|p| *p != 0.0),
+            Column::Change => quote.change,
+            Column::ChangePercent => quote.change_percent,
+            Column::Volume => quote.volume.map(|v| v as f64),
+            Column::Range => ticker.range_position(),
+            Column::MarketCap => ticker.market_cap(),
+        }
+    }
+
+    /// Text of the cell, `-` where the value is not available.
+    pub fn cell(&self, ticker: &Ticker, quote: &Quote) -> String {
+        let value = self.value(ticker, quote);
+
+        match self {
+            Column::Symbol => Some(ticker.identifier().clone()),
+            Column::Name => match ticker.info().name().is_empty() {
+                true => Some(ticker.identifier().clone()),
+                false => Some(ticker.info().name().clone()),
+            },
+            Column::Last => value.map(|v| format!("{:.2}", v)),
+            Column::Change => value.map(|v| format!("{:+.2}", v)),
+            Column::ChangePercent => value.map(|v| format!("{:+.2}%", v)),
+            Column::Volume => value.map(compact),
+            Column::Range => value.map(|v| format!("{:.0}%", v * 100.0)),
+            Column::MarketCap => value.map(compact),
+        }
+        .unwrap_or_else(|| "-".into())
+    }
+
+    /// What the column sorts by: the value of numeric columns, the lowercase
+    /// text of the others.
+    fn key(&self, ticker: &Ticker) -> (Option<f64>, String) {
+        let quote = Quote::from(ticker);
+
+        match self.is_numeric() {
+            true => (self.value(ticker, &quote), String::new()),
+            false => (None, self.cell(ticker, &quote).to_lowercase()),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let idx = match self {
+            Column::Symbol => 0,
+            Column::Name => 1,
+            Column::Last => 2,
+            Column::Change => 3,
+            Column::ChangePercent => 4,
+            Column::Volume => 5,
+            Column::Range => 6,
+            Column::MarketCap => 7,
+        };
+
+        write!(f, "{}", Column::NAMES[idx])
+    }
+}
+
+impl FromStr for Column {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "symbol" => Ok(Column::Symbol),
+            "name" => Ok(Column::Name),
+            "last" | "price" => Ok(Column::Last),
+            "change" => Ok(Column::Change),
+            "change_percent" | "change%" => Ok(Column::ChangePercent),
+            "volume" => Ok(Column::Volume),
+            "range_52w" | "52w" => Ok(Column::Range),
+            "market_cap" => Ok(Column::MarketCap),
+            _ => Err(()),
+        }
+    }
+}
+
+pub fn default_columns() -> Vec<Column> {
+    vec![Column::Symbol, Column::Last, Column::ChangePercent]
+}
+
+/// Sort order of the watchlist: a column and its direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub column: Column,
+    pub descending: bool,
+}
+
+/// Columns and sort order of the watchlist table.
+#[derive(Debug, Clone)]
+pub struct Watchlist {
+    columns: Vec<Column>,
+    sort: Option<Sort>,
+}
+
+impl Watchlist {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            columns,
+            sort: None,
+        }
+    }
+
+    pub fn columns(&self) -> &Vec<Column> {
+        &self.columns
+    }
+
+    pub fn sort(&self) -> Option<Sort> {
+        self.sort
+    }
+
+    /// Sorts by the next column, going back to the configured order after the
+    /// last one. Numbers sort descending first, text ascending.
+    pub fn cycle_sort(&mut self) {
+        let idx = match self.sort {
+            Some(sort) => self.columns.iter().position(|c| *c == sort.column).map(|idx| idx + 1).unwrap_or(0),
+            None => 0,
+        };
+
+        self.sort = self.columns.get(idx).map(|column| Sort {
+            column: *column,
+            descending: column.is_numeric(),
+        });
+    }
+
+    pub fn reverse(&mut self) {
+        if let Some(sort) = self.sort.as_mut() {
+            sort.descending = !sort.descending;
+        }
+    }
+
+    /// Indices of `tickers` in display order. Missing values go last in either
+    /// direction, and ties keep the configured order.
+    pub fn order(&self, tickers: &[Ticker]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..tickers.len()).collect();
+
+        if let Some(sort) = self.sort {
+            // keyed once per ticker rather than on every comparison
+            let keys: Vec<(Option<f64>, String)> = tickers.iter().map(|t| sort.column.key(t)).collect();
+
+            order.sort_by(|a, b| {
+                let (a, b) = (&keys[*a], &keys[*b]);
+
+                match (a.0.is_some(), b.0.is_some()) {
+                    (true, false) if sort.column.is_numeric() => Ordering::Less,
+                    (false, true) if sort.column.is_numeric() => Ordering::Greater,
+                    _ => match sort.descending {
+                        true => b.partial_cmp(a),
+                        false => a.partial_cmp(b),
+                    }
+                    .unwrap_or(Ordering::Equal),
+                }
+            });
+        }
+
+        order
+    }
+
+    /// Index of the ticker `offset` rows away from `selected` in display
+    /// order, stopping at either end.
+    pub fn step(&self, tickers: &[Ticker], selected: usize, offset: isize) -> usize {
+        let order = self.order(tickers);
+        let position = order.iter().position(|idx| *idx == selected).unwrap_or(0) as isize;
+        let position = (position + offset).max(0).min(order.len() as isize - 1);
+
+        order.get(position as usize).copied().unwrap_or(selected)
+    }
+}
+
+/// Formats large numbers with a K, M, B or T suffix.
+fn compact(value: f64) -> String {
+    let units = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+
+    match units.iter().find(|(size, _)| value.abs() >= *size) {
+        Some((size, unit)) => format!("{:.1}{}", value / size, unit),
+        None => format!("{:.0}", value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticker::Data;
+    use ordered_float::OrderedFloat;
+
+    /// A ticker whose last price is the last of `closes`.
+    fn ticker(symbol: &str, closes: &[f64]) -> Ticker {
+        let mut ticker = Ticker::new(symbol.into());
+        ticker.set_data(Data::new(
+            closes.iter().map(|c| OrderedFloat::from(*c)).collect(),
+            closes.iter().map(|_| String::new()).collect(),
+            closes.iter().map(|_| 0).collect(),
+            (0..closes.len() as i64).collect(),
+        ));
+        ticker
+    }
+
+    /// Last prices 20, 30 and 10, and one without a price.
+    fn tickers() -> Vec<Ticker> {
+        vec![
+            ticker("msft", &[10.0, 20.0]),
+            ticker("AAPL", &[10.0, 30.0]),
+            ticker("NONE", &[]),
+            ticker("TSLA", &[20.0, 10.0]),
+        ]
+    }
+
+    #[test]
+    fn order_is_configured_until_sorted() {
+        let watchlist = Watchlist::new(vec![Column::Symbol, Column::Last]);
+        assert_eq!(watchlist.order(&tickers()), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn order_puts_missing_values_last() {
+        let mut watchlist = Watchlist::new(vec![Column::Last]);
+        watchlist.cycle_sort();
+
+        assert_eq!(watchlist.sort(), Some(Sort { column: Column::Last, descending: true }));
+        assert_eq!(watchlist.order(&tickers()), vec![1, 0, 3, 2]);
+
+        watchlist.reverse();
+        assert_eq!(watchlist.order(&tickers()), vec![3, 0, 1, 2]);
+    }
+
+    #[test]
+    fn order_sorts_text_ignoring_case_and_keeps_ties() {
+        let mut watchlist = Watchlist::new(vec![Column::Symbol, Column::Change]);
+        watchlist.cycle_sort();
+
+        assert_eq!(watchlist.order(&tickers()), vec![1, 0, 2, 3]);
+
+        // B and A change alike
+        let tied = vec![ticker("B", &[1.0, 2.0]), ticker("A", &[1.0, 2.0]), ticker("C", &[2.0, 1.0])];
+        watchlist.cycle_sort();
+        assert_eq!(watchlist.order(&tied), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn cycle_sort_goes_back_to_the_configured_order() {
+        let mut watchlist = Watchlist::new(vec![Column::Symbol, Column::Last]);
+
+        watchlist.cycle_sort();
+        assert_eq!(watchlist.sort(), Some(Sort { column: Column::Symbol, descending: false }));
+
+        watchlist.cycle_sort();
+        assert_eq!(watchlist.sort(), Some(Sort { column: Column::Last, descending: true }));
+
+        watchlist.cycle_sort();
+        assert_eq!(watchlist.sort(), None);
+
+        let mut empty = Watchlist::new(vec![]);
+        empty.cycle_sort();
+        assert_eq!(empty.sort(), None);
+    }
+
+    #[test]
+    fn step_follows_the_display_order() {
+        let mut watchlist = Watchlist::new(vec![Column::Last]);
+        let tickers = tickers();
+
+        assert_eq!(watchlist.step(&tickers, 0, 1), 1);
+
+        watchlist.cycle_sort();
+        assert_eq!(watchlist.step(&tickers, 1, 1), 0);
+        assert_eq!(watchlist.step(&tickers, 0, 1), 3);
+        assert_eq!(watchlist.step(&tickers, 1, -1), 1);
+        assert_eq!(watchlist.step(&tickers, 3, 5), 2);
+        assert_eq!(watchlist.step(&[], 0, 1), 0);
+    }
+
+    #[test]
+    fn selection_survives_a_re_sort() {
+        let mut watchlist = Watchlist::new(vec![Column::Symbol, Column::Last]);
+        let tickers = tickers();
+        let selected = 3;
+        let mut rows = vec![];
+
+        for _ in 0..3 {
+            watchlist.cycle_sort();
+
+            let order = watchlist.order(&tickers);
+            let row = order.iter().position(|idx| *idx == selected).unwrap();
+
+            assert_eq!(tickers[order[row]].identifier(), "TSLA");
+            assert_eq!(watchlist.step(&tickers, selected, 0), selected);
+            rows.push(row);
+        }
+
+        // by symbol, by last price, then in the configured order
+        assert_eq!(rows, vec![3, 2, 3]);
+    }
+}